@@ -26,11 +26,19 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("decode_buf random 1024", move |b| b.iter(|| {
         corncobs::decode_buf(black_box(random_enc_1024), &mut out).unwrap();
     }));
+    c.bench_function("decode_buf strict random 1024", move |b| b.iter(|| {
+        let strict = corncobs::DecodeOptions::new().strict(true);
+        strict.decode_buf(black_box(random_enc_1024), &mut out).unwrap();
+    }));
     c.bench_function("decode_in_place random 1024", move |b| b.iter_batched(
         || random_enc_1024.to_vec(),
         |mut data| corncobs::decode_in_place(&mut data).unwrap(),
         criterion::BatchSize::SmallInput,
     ));
+    c.bench_function("Decoder::feed random 1024", move |b| b.iter(|| {
+        let mut decoder = corncobs::Decoder::default();
+        decoder.feed(black_box(random_enc_1024), &mut out).unwrap();
+    }));
 
 
     let mut out = [0; corncobs::max_encoded_len(ZERO_1024.len())];
@@ -53,6 +61,20 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         criterion::BatchSize::SmallInput,
     ));
 
+    let mut out = [0; corncobs::zpe::max_encoded_len(ZERO_1024.len())];
+    c.bench_function("zpe::encode_buf zero 1024", move |b| b.iter(|| {
+        corncobs::zpe::encode_buf(black_box(&ZERO_1024), &mut out);
+    }));
+
+    let mut zpe_zero_enc_1024 = [0; corncobs::zpe::max_encoded_len(ZERO_1024.len())];
+    let n = corncobs::zpe::encode_buf(&ZERO_1024, &mut zpe_zero_enc_1024);
+    let zpe_zero_enc_1024 = &zpe_zero_enc_1024[..n];
+
+    let mut out = [0; ZERO_1024.len()];
+    c.bench_function("zpe::decode_buf zero 1024", move |b| b.iter(|| {
+        corncobs::zpe::decode_buf(black_box(zpe_zero_enc_1024), &mut out).unwrap();
+    }));
+
 
     let mut out = [0; corncobs::max_encoded_len(FF_1024.len())];
     c.bench_function("encode_buf ff 1024", move |b| b.iter(|| {
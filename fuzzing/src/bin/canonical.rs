@@ -0,0 +1,28 @@
+//! Check that `encode_buf` output is always canonical, and that anything
+//! `is_canonical` accepts survives a decode/encode round trip unchanged.
+
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut encoded = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = corncobs::encode_buf(data, &mut encoded);
+            assert!(corncobs::is_canonical(&encoded[..n]));
+
+            let canonical = corncobs::DecodeOptions::new().canonical(true);
+            let mut decoded = vec![0; data.len()];
+            let result = canonical.decode_buf_with_consumed(data, &mut decoded);
+            if corncobs::is_canonical(data) {
+                let d = result.unwrap();
+                assert_eq!(d.consumed, data.len());
+                let mut reencoded = vec![0; corncobs::max_encoded_len(d.decoded_len)];
+                let n = corncobs::encode_buf(&decoded[..d.decoded_len], &mut reencoded);
+                assert_eq!(&reencoded[..n], data);
+            } else if let Ok(d) = result {
+                // The only other way to pass is to have trailing data.
+                assert!(d.consumed < data.len());
+            }
+        });
+    }
+}
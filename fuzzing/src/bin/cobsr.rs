@@ -0,0 +1,25 @@
+//! Check that COBS/R round-trips arbitrary input, and that the COBS/R decoders
+//! agree with each other on arbitrary (probably invalid) input.
+
+use honggfuzz::fuzz;
+
+use corncobs::cobsr;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut encoded = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = cobsr::encode_buf(data, &mut encoded);
+            assert!(n <= corncobs::encoded_len(data));
+            let mut decoded = vec![0; n];
+            let len = cobsr::decode_buf(&encoded[..n], &mut decoded).unwrap();
+            assert_eq!(&decoded[..len], data);
+
+            let mut out = vec![0; data.len()];
+            let buf = cobsr::decode_buf(data, &mut out).map(|n| out[..n].to_vec());
+            let mut scratch = data.to_vec();
+            let in_place = cobsr::decode_in_place(&mut scratch).map(|n| scratch[..n].to_vec());
+            assert_eq!(in_place, buf);
+        });
+    }
+}
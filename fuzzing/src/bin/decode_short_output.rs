@@ -0,0 +1,21 @@
+//! Decode into an output buffer that is (probably) too small, checking that we
+//! get an error rather than a panic, and that the size we're told we need is
+//! right.
+
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let len = usize::from(data.first().copied().unwrap_or(0));
+            let mut out = vec![0; len];
+            if let Err(corncobs::CobsError::OutputTooSmall { needed }) =
+                corncobs::decode_buf(data, &mut out)
+            {
+                assert!(needed > len);
+                let mut out = vec![0; needed];
+                assert_eq!(corncobs::decode_buf(data, &mut out), Ok(needed));
+            }
+        });
+    }
+}
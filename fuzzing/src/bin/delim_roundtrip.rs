@@ -0,0 +1,30 @@
+//! Encode arbitrary input with a non-zero delimiter, and check that it matches
+//! XORing around standard COBS and that it round-trips.
+
+use honggfuzz::fuzz;
+
+use corncobs::Cobs;
+
+type FlashCobs = Cobs<0xFF>;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut encoded = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = FlashCobs::encode_buf(data, &mut encoded);
+            encoded.truncate(n);
+            assert_eq!(encoded.iter().position(|&b| b == 0xFF), Some(n - 1));
+
+            let xored: Vec<u8> = data.iter().map(|b| b ^ 0xFF).collect();
+            let mut expected = vec![0; corncobs::max_encoded_len(data.len())];
+            let m = corncobs::encode_buf(&xored, &mut expected);
+            expected.truncate(m);
+            expected.iter_mut().for_each(|b| *b ^= 0xFF);
+            assert_eq!(encoded, expected);
+
+            let mut decoded = vec![0; data.len()];
+            let len = FlashCobs::decode_buf(&encoded, &mut decoded).unwrap();
+            assert_eq!(&decoded[..len], data);
+        });
+    }
+}
@@ -0,0 +1,17 @@
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut out0 = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = corncobs::encode_buf(data, &mut out0);
+
+            let mut out1 = vec![0; corncobs::max_encoded_len(data.len())];
+            let start = out1.len() - data.len();
+            out1[start..].copy_from_slice(data);
+            let m = corncobs::encode_in_place(&mut out1, data.len());
+
+            assert_eq!(&out0[..n], &out1[..m]);
+        });
+    }
+}
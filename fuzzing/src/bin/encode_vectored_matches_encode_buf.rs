@@ -0,0 +1,22 @@
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut out0 = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = corncobs::encode_buf(data, &mut out0);
+
+            // Use the first two bytes to pick split points.
+            let a = usize::from(data.first().copied().unwrap_or(0)).min(data.len());
+            let b = (a + usize::from(data.get(1).copied().unwrap_or(0))).min(data.len());
+            let parts = [&data[..a], &data[a..b], &data[b..]];
+
+            let mut out1 = vec![0; corncobs::max_encoded_len(data.len())];
+            let m = corncobs::encode_vectored_buf(&parts, &mut out1);
+            assert_eq!(&out0[..n], &out1[..m]);
+
+            let out2: Vec<u8> = corncobs::encode_vectored_iter(&parts).collect();
+            assert_eq!(&out0[..n], out2);
+        });
+    }
+}
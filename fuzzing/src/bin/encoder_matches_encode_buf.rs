@@ -0,0 +1,22 @@
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut out0 = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = corncobs::encode_buf(data, &mut out0);
+
+            // Use the first byte to pick a chunk size, to exercise runs that
+            // span calls to `push`.
+            let chunk = usize::from(data.first().copied().unwrap_or(1)).max(1);
+            let mut out1 = vec![];
+            let mut enc = corncobs::Encoder::new(|b: &[u8]| out1.extend_from_slice(b));
+            for piece in data.chunks(chunk) {
+                enc.push(piece);
+            }
+            enc.finish();
+
+            assert_eq!(&out0[..n], out1);
+        });
+    }
+}
@@ -0,0 +1,28 @@
+//! Check that the rCOBS encoders agree with each other and round-trip
+//! arbitrary input, and that the rCOBS decoders agree on arbitrary input.
+
+use honggfuzz::fuzz;
+
+use corncobs::rcobs;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut encoded = vec![0; corncobs::max_encoded_len(data.len())];
+            let n = rcobs::encode_buf(data, &mut encoded);
+            let encoded = &encoded[..n];
+            assert_eq!(n, corncobs::encoded_len(data));
+            assert!(rcobs::encode_iter(data).eq(encoded.iter().copied()));
+
+            let mut decoded = vec![0; n];
+            let len = rcobs::decode_buf(encoded, &mut decoded).unwrap();
+            assert_eq!(&decoded[..len], data);
+
+            let mut out = vec![0; data.len()];
+            let buf = rcobs::decode_buf(data, &mut out).map(|n| out[..n].to_vec());
+            let mut scratch = data.to_vec();
+            let in_place = rcobs::decode_in_place(&mut scratch).map(|n| scratch[..n].to_vec());
+            assert_eq!(in_place, buf);
+        });
+    }
+}
@@ -0,0 +1,45 @@
+//! Decode arbitrary input with each of the strict decode paths, and check that
+//! they agree with each other and with `Decoder`.
+
+use honggfuzz::fuzz;
+
+use corncobs::{CobsError, DecodeOptions, Decoder};
+
+fn main() {
+    let strict = DecodeOptions::new().strict(true);
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut out = vec![0; data.len()];
+            let buf = strict
+                .decode_buf_with_consumed(data, &mut out)
+                .map(|d| (out[..d.decoded_len].to_vec(), d.consumed));
+
+            let mut scratch = data.to_vec();
+            let in_place = strict
+                .decode_in_place_with_consumed(&mut scratch)
+                .map(|d| (scratch[..d.decoded_len].to_vec(), d.consumed));
+            assert_eq!(in_place, buf);
+
+            let plain = strict.decode_buf(data, &mut out).map(|n| out[..n].to_vec());
+            assert_eq!(plain, buf.clone().map(|(d, _)| d));
+            let mut scratch = data.to_vec();
+            let plain_in_place = strict
+                .decode_in_place(&mut scratch)
+                .map(|n| scratch[..n].to_vec());
+            assert_eq!(plain_in_place, plain);
+
+            let (a, b) = data.split_at(data.len() / 2);
+            let ring = strict
+                .decode_ring_buf((a, b), &mut out)
+                .map(|d| (out[..d.decoded_len].to_vec(), d.consumed.0 + d.consumed.1));
+            assert_eq!(ring, buf);
+
+            let stream = match Decoder::new().feed(data, &mut out) {
+                Ok(r) if r.frame_done => Ok((out[..r.produced].to_vec(), r.consumed)),
+                Ok(_) => Err(CobsError::Truncated),
+                Err(e) => Err(e.error),
+            };
+            assert_eq!(stream, buf);
+        });
+    }
+}
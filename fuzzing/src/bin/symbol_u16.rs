@@ -0,0 +1,29 @@
+//! Treat arbitrary input as `u16` symbols, and check that it round-trips
+//! through the symbol-generic encoder and both decoders.
+
+use honggfuzz::fuzz;
+
+use corncobs::symbol;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let symbols: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+
+            let mut encoded = vec![0; symbol::max_encoded_len::<u16>(symbols.len())];
+            let n = symbol::encode_buf(&symbols, &mut encoded);
+            let encoded = &encoded[..n];
+
+            let mut decoded = vec![0; n];
+            let len = symbol::decode_buf(encoded, &mut decoded).unwrap();
+            assert_eq!(&decoded[..len], &symbols[..]);
+
+            let r = symbol::decoder::<u16>().feed(encoded, &mut decoded).unwrap();
+            assert!(r.frame_done);
+            assert_eq!(&decoded[..r.produced], &symbols[..]);
+        });
+    }
+}
@@ -0,0 +1,18 @@
+//! Check that `validate` reaches the same conclusion as `decode_buf` about
+//! arbitrary input.
+
+use honggfuzz::fuzz;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut out = vec![0; data.len()];
+            let decoded = corncobs::decode_buf_with_consumed(data, &mut out);
+            let validated = corncobs::validate(data).map(|info| corncobs::Decoded {
+                decoded_len: info.decoded_len,
+                consumed: info.consumed,
+            });
+            assert_eq!(validated, decoded);
+        });
+    }
+}
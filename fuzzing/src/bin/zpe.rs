@@ -0,0 +1,36 @@
+//! Check that COBS/ZPE round-trips arbitrary input, and that `zpe::decode_buf`
+//! and `zpe::Decoder` agree on any input.
+
+use honggfuzz::fuzz;
+
+use corncobs::zpe;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut encoded = vec![0; zpe::max_encoded_len(data.len())];
+            let n = zpe::encode_buf(data, &mut encoded);
+            let encoded = &encoded[..n];
+
+            let mut decoded = vec![0; zpe::max_decoded_len(n)];
+            let len = zpe::decode_buf(encoded, &mut decoded).unwrap();
+            assert_eq!(&decoded[..len], data);
+
+            let r = zpe::Decoder::new().feed(encoded, &mut decoded).unwrap();
+            assert!(r.frame_done);
+            assert_eq!(&decoded[..r.produced], data);
+
+            // Arbitrary input mustn't panic, and both decoders should reach the
+            // same verdict on it.
+            let mut out = vec![0; zpe::max_decoded_len(data.len())];
+            let buf = zpe::decode_buf_with_consumed(data, &mut out)
+                .map(|d| (out[..d.decoded_len].to_vec(), d.consumed));
+            let stream = match zpe::Decoder::new().feed(data, &mut out) {
+                Ok(r) if r.frame_done => Ok((out[..r.produced].to_vec(), r.consumed)),
+                Ok(_) => Err(corncobs::CobsError::Truncated),
+                Err(e) => Err(e.error),
+            };
+            assert_eq!(buf, stream);
+        });
+    }
+}
@@ -0,0 +1,242 @@
+//! COBS/R, or "reduced" COBS: a variant that often saves a byte at the end of
+//! each message.
+//!
+//! In standard COBS, every message ends with a run whose length byte is
+//! somewhere between `0x01` and `0xFF`. COBS/R notices when the _last byte_ of
+//! the message is at least as large as that length byte, and, if so, moves
+//! that last byte into the length byte's position and drops it from the end.
+//! The decoder can tell this has happened because the terminator arrives before
+//! the run is finished; when it does, the length byte gets tacked onto the end
+//! of the message. For small messages whose last byte is more or less random,
+//! this saves a byte most of the time.
+//!
+//! COBS/R output is never longer than standard COBS, so
+//! [`max_encoded_len`](crate::max_encoded_len) works for sizing buffers here
+//! too, and decoding never needs more than `bytes.len()` bytes of output.
+//!
+//! The encoding matches Craig McQueen's reference implementations (the `cobsr`
+//! C library and Python package), with the addition of the zero terminator, as
+//! elsewhere in this crate. If you're talking to one of those, strip or add the
+//! terminator at the edges.
+//!
+//! ```
+//! use corncobs::cobsr;
+//!
+//! let mut encoded = [0; corncobs::max_encoded_len(3)];
+//! let n = cobsr::encode_buf(&[0x11, 0x22, 0x33], &mut encoded);
+//! // Standard COBS would produce 04 11 22 33 00.
+//! assert_eq!(&encoded[..n], [0x33, 0x11, 0x22, 0x00]);
+//!
+//! let mut decoded = [0; 3];
+//! assert_eq!(cobsr::decode_buf(&encoded[..n], &mut decoded), Ok(3));
+//! assert_eq!(decoded, [0x11, 0x22, 0x33]);
+//! ```
+//!
+//! Unlike standard COBS, decoding COBS/R has to look at every byte, to find out
+//! where the message ends. As a result, the decoders here always check for
+//! unexpected zeroes, like [`DecodeOptions::strict`](crate::DecodeOptions::strict)
+//! -- the only zero they'll accept in a run is the one that ends a reduced
+//! message.
+
+use crate::{decode_len, encode_len, CobsError, Decoded, MAX_RUN, ZERO};
+
+/// Encodes the message `bytes` into the buffer `output` using COBS/R. Returns
+/// the number of bytes used in `output`, which also happens to be the index of
+/// the first zero byte.
+///
+/// This works like [`encode_buf`](crate::encode_buf), and has the same
+/// requirements for the size of `output`.
+///
+/// # Panics
+///
+/// If `output` is too small to contain the encoded form of `input`, which is
+/// `max_encoded_len(bytes.len())` bytes worst-case.
+pub fn encode_buf(bytes: &[u8], output: &mut [u8]) -> usize {
+    let reduction = reduction(bytes);
+    let len = crate::encode_buf(bytes, output);
+    reduce(output, len, reduction)
+}
+
+/// Encodes `bytes` into the vector `output` using COBS/R. This is a
+/// convenience for cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn encode(bytes: &[u8], output: &mut Vec<u8>) {
+    let offset = output.len();
+    output.resize(offset + crate::max_encoded_len(bytes.len()), 0);
+    let actual_len = encode_buf(bytes, &mut output[offset..]);
+    output.truncate(offset + actual_len);
+}
+
+/// Encodes a message in-place using COBS/R.
+///
+/// This works like [`encode_in_place`](crate::encode_in_place): the message
+/// must occupy the last `payload_len` bytes of `buf`, and the encoded message
+/// is deposited into `buf` starting at index 0.
+///
+/// # Panics
+///
+/// If `buf` is shorter than `max_encoded_len(payload_len)`.
+pub fn encode_in_place(buf: &mut [u8], payload_len: usize) -> usize {
+    // Work this out before encoding scribbles over the message.
+    let reduction = reduction(&buf[buf.len() - payload_len..]);
+    let len = crate::encode_in_place(buf, payload_len);
+    reduce(buf, len, reduction)
+}
+
+/// Decides whether COBS/R can save a byte on the message `bytes`. If so,
+/// returns the message's last byte, which will stand in for the final length
+/// byte, and the length of the final run.
+fn reduction(bytes: &[u8]) -> Option<(u8, usize)> {
+    // The final run is whatever comes after the last zero, less any maximal
+    // runs in front of it. A maximal final run is left alone; so is an empty
+    // one, since there's no last byte to move.
+    let tail = bytes.rsplit(|&b| b == ZERO).next()?;
+    let n = tail.len() % MAX_RUN;
+    let last = *tail.last()?;
+    if n != 0 && last >= encode_len(n) {
+        Some((last, n))
+    } else {
+        None
+    }
+}
+
+/// Applies `reduction` to the standard encoding of a message, which occupies
+/// the first `len` bytes of `output`. Returns the new length.
+fn reduce(output: &mut [u8], len: usize, reduction: Option<(u8, usize)>) -> usize {
+    if let Some((last, n)) = reduction {
+        // The final run sits just before the terminator, with its length byte
+        // in front of it.
+        output[len - 2 - n] = last;
+        output[len - 2] = ZERO;
+        len - 1
+    } else {
+        len
+    }
+}
+
+/// Decodes COBS/R input from `bytes` into `output` starting at index 0.
+/// Returns the number of bytes used in `output`.
+///
+/// This works like [`decode_buf`](crate::decode_buf), including reporting
+/// `CobsError::OutputTooSmall` if the message doesn't fit in `output`.
+pub fn decode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+    decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+}
+
+/// Decodes COBS/R input from `bytes` into `output` starting at index 0, and
+/// also reports how many bytes of `bytes` made up the message, including the
+/// terminator, like
+/// [`decode_buf_with_consumed`](crate::decode_buf_with_consumed).
+pub fn decode_buf_with_consumed(bytes: &[u8], output: &mut [u8]) -> Result<Decoded, CobsError> {
+    let mut inpos = 0;
+    let mut outpos = 0;
+    let mut trailing_zero = false;
+    // Once we run out of output, we keep going without writing anything, to
+    // find out how much would have been enough.
+    let mut overflow = false;
+    loop {
+        let (n, reduced) = match group(&bytes[inpos..])? {
+            Group::End => break,
+            Group::Run(n) => (n, false),
+            Group::Reduced(n) => (n, true),
+        };
+        let start = outpos + usize::from(trailing_zero);
+        let end = start + n + usize::from(reduced);
+        overflow |= output.len() < end;
+        if !overflow {
+            if trailing_zero {
+                output[outpos] = ZERO;
+            }
+            output[start..start + n].copy_from_slice(&bytes[inpos + 1..inpos + 1 + n]);
+            if reduced {
+                output[end - 1] = bytes[inpos];
+            }
+        }
+        inpos += 1 + n;
+        outpos = end;
+        trailing_zero = n != MAX_RUN;
+    }
+
+    if overflow {
+        Err(CobsError::OutputTooSmall { needed: outpos })
+    } else {
+        Ok(Decoded {
+            decoded_len: outpos,
+            consumed: inpos + 1,
+        })
+    }
+}
+
+/// Decodes COBS/R input from `bytes` into a vector. This is a convenience for
+/// cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
+    let offset = output.len();
+    output.resize(offset + bytes.len(), 0);
+    let actual_len = decode_buf(bytes, &mut output[offset..])?;
+    output.truncate(offset + actual_len);
+    Ok(())
+}
+
+/// Decodes a COBS/R message in-place, like
+/// [`decode_in_place`](crate::decode_in_place). Returns the number of decoded
+/// bytes, which are deposited at the start of `bytes`.
+///
+/// Unlike `decode_in_place`, this requires the terminator to be present, and
+/// returns `CobsError::Truncated` if it isn't: without it, there's no telling
+/// whether the last run was reduced.
+pub fn decode_in_place(bytes: &mut [u8]) -> Result<usize, CobsError> {
+    let mut inpos = 0;
+    let mut outpos = 0;
+    let mut trailing_zero = false;
+    loop {
+        let (n, reduced) = match group(&bytes[inpos..])? {
+            Group::End => break,
+            Group::Run(n) => (n, false),
+            Group::Reduced(n) => (n, true),
+        };
+        // Each length byte we read makes room for the zero or final byte we
+        // write, so we stay behind the position we're reading from.
+        let head = bytes[inpos];
+        if trailing_zero {
+            bytes[outpos] = ZERO;
+            outpos += 1;
+        }
+        bytes.copy_within(inpos + 1..inpos + 1 + n, outpos);
+        outpos += n;
+        if reduced {
+            bytes[outpos] = head;
+            outpos += 1;
+        }
+        inpos += 1 + n;
+        trailing_zero = n != MAX_RUN;
+    }
+    Ok(outpos)
+}
+
+/// What we find at the start of a group in a COBS/R message.
+enum Group {
+    /// The terminator.
+    End,
+    /// A length byte followed by a run of this many bytes, as in standard COBS.
+    Run(usize),
+    /// A length byte followed by this many bytes and then the terminator,
+    /// before the run was due to end. The length byte is really the last byte
+    /// of the message.
+    Reduced(usize),
+}
+
+/// Reads the group at the start of `bytes`, checking its run for zeroes.
+fn group(bytes: &[u8]) -> Result<Group, CobsError> {
+    let (&head, rest) = bytes.split_first().ok_or(CobsError::Truncated)?;
+    let n = match decode_len(head) {
+        Some(n) => n,
+        None => return Ok(Group::End),
+    };
+    let avail = usize::min(n, rest.len());
+    match rest[..avail].iter().position(|&b| b == ZERO) {
+        Some(i) => Ok(Group::Reduced(i)),
+        None if avail < n => Err(CobsError::Truncated),
+        None => Ok(Group::Run(n)),
+    }
+}
@@ -0,0 +1,134 @@
+//! COBS with a delimiter other than zero.
+
+use crate::{
+    decode_buf_core, decode_in_place_inner, encode_buf_core, encode_in_place_core,
+    encode_iter_core, CobsError, Decoded, Decoder,
+};
+
+/// COBS encoding and decoding using `DELIM`, rather than zero, as the frame
+/// delimiter.
+///
+/// Standard COBS removes zeroes from a message so that zero can mark the end of
+/// it. Sometimes a different byte is more convenient for this: for example,
+/// `0xFF` is the erased state of most flash memory, so using it as the
+/// delimiter in a flash-backed log means that the end of the log is also the
+/// end of the last message. `Cobs::<0xFF>` gives you that.
+///
+/// The encoding is the same as XORing each byte of the message with `DELIM`,
+/// encoding it with standard COBS, and XORing each byte of the result with
+/// `DELIM` again. In other words, the message is split on `DELIM` bytes rather
+/// than zeroes, the length bytes are XORed with `DELIM` (so they can't be
+/// `DELIM`), the message bytes are copied unchanged, and the frame ends with
+/// `DELIM`. `Cobs::<0>` is standard COBS, the same as the free functions in
+/// this crate.
+///
+/// This type is never constructed; it just groups the functions for each
+/// delimiter. It has versions of the main buffer, iterator, and in-place
+/// routines, and a [`Decoder`] for streams. These work just like their
+/// zero-delimited counterparts, so see those for details.
+///
+/// ```
+/// use corncobs::Cobs;
+///
+/// type FlashCobs = Cobs<0xFF>;
+///
+/// let msg = [0x11, 0xFF, 0x00, 0x22];
+/// let mut encoded = [0; corncobs::max_encoded_len(4)];
+/// let n = FlashCobs::encode_buf(&msg, &mut encoded);
+/// assert_eq!(&encoded[..n], [0xFD, 0x11, 0xFC, 0x00, 0x22, 0xFF]);
+///
+/// let mut decoded = [0; 4];
+/// assert_eq!(FlashCobs::decode_buf(&encoded[..n], &mut decoded), Ok(4));
+/// assert_eq!(decoded, msg);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub enum Cobs<const DELIM: u8> {}
+
+impl<const DELIM: u8> Cobs<DELIM> {
+    /// The delimiter used by this variant.
+    pub const DELIM: u8 = DELIM;
+
+    /// Encodes `bytes` into `output`, like [`encode_buf`](crate::encode_buf).
+    ///
+    /// # Panics
+    ///
+    /// If `output` is too small to contain the encoded form of `input`, which
+    /// is `max_encoded_len(bytes.len())` bytes worst-case.
+    pub fn encode_buf(bytes: &[u8], output: &mut [u8]) -> usize {
+        encode_buf_core::<u8, DELIM>(bytes, output)
+    }
+
+    /// Encodes `bytes` into the vector `output`, like
+    /// [`encode`](crate::encode).
+    #[cfg(feature = "std")]
+    pub fn encode(bytes: &[u8], output: &mut Vec<u8>) {
+        let offset = output.len();
+        output.resize(offset + crate::max_encoded_len(bytes.len()), 0);
+        let actual_len = Self::encode_buf(bytes, &mut output[offset..]);
+        output.truncate(offset + actual_len);
+    }
+
+    /// Encodes `bytes`, yielding individual encoded bytes through an iterator,
+    /// like [`encode_iter`](crate::encode_iter).
+    pub fn encode_iter(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+        encode_iter_core::<DELIM>(bytes)
+    }
+
+    /// Encodes a message in-place, like
+    /// [`encode_in_place`](crate::encode_in_place). The message must occupy the
+    /// last `payload_len` bytes of `buf`.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is shorter than `max_encoded_len(payload_len)`.
+    pub fn encode_in_place(buf: &mut [u8], payload_len: usize) -> usize {
+        encode_in_place_core::<DELIM>(buf, payload_len)
+    }
+
+    /// Decodes input from `bytes` into `output` starting at index 0, like
+    /// [`decode_buf`](crate::decode_buf).
+    pub fn decode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+        Self::decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+    }
+
+    /// Decodes input from `bytes` into `output` starting at index 0, and also
+    /// reports how much of `bytes` was used, like
+    /// [`decode_buf_with_consumed`](crate::decode_buf_with_consumed).
+    pub fn decode_buf_with_consumed(
+        bytes: &[u8],
+        output: &mut [u8],
+    ) -> Result<Decoded, CobsError> {
+        decode_buf_core::<u8, false, false, DELIM>(bytes, output).map_err(|e| e.kind)
+    }
+
+    /// Decodes `bytes` into a vector, like [`decode`](crate::decode).
+    #[cfg(feature = "std")]
+    pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
+        let offset = output.len();
+        output.resize(offset + bytes.len(), 0);
+        let actual_len = Self::decode_buf(bytes, &mut output[offset..])?;
+        output.truncate(offset + actual_len);
+        Ok(())
+    }
+
+    /// Decodes an encoded message in-place, like
+    /// [`decode_in_place`](crate::decode_in_place).
+    pub fn decode_in_place(bytes: &mut [u8]) -> Result<usize, CobsError> {
+        decode_in_place_inner::<u8, false, false, DELIM>(bytes)
+            .map(|(decoded_len, _)| decoded_len)
+            .map_err(|e| e.kind)
+    }
+
+    /// Creates an incremental [`Decoder`] for this delimiter.
+    ///
+    /// ```
+    /// let mut decoder = corncobs::Cobs::<0x7E>::decoder();
+    /// let mut output = [0; 4];
+    /// let r = decoder.feed(&[0x7C, 0x11, 0x7E], &mut output).unwrap();
+    /// assert!(r.frame_done);
+    /// assert_eq!(&output[..r.produced], [0x11]);
+    /// ```
+    pub fn decoder() -> Decoder<DELIM> {
+        Decoder::empty()
+    }
+}
@@ -0,0 +1,252 @@
+//! Iterating over all the frames in a buffer.
+
+use core::ops::Range;
+
+use crate::{decode_buf, CobsError, DecodeError, DecodeOptions, DecodeStatus, Decoder, ZERO};
+
+/// Returns an iterator over the encoded frames in `bytes`, which may contain
+/// any number of frames back to back.
+///
+/// Each frame is checked as it's found, and yielded as either a [`Frame`] or
+/// the error that was found in it, as a [`DecodeError`] whose offset is from
+/// the start of `bytes`. Frames are delimited by zeroes, so after a bad frame,
+/// iteration picks up at the next zero, just like a streaming receiver would.
+/// If `bytes` ends partway through a frame, the last item is a
+/// `CobsError::Truncated` error.
+///
+/// Checking a frame doesn't decode it; you can do that using the methods on
+/// `Frame`. If you don't need `bytes` afterwards, [`frames_in_place`] decodes
+/// as it goes, without needing extra memory. To check frames more strictly,
+/// use [`DecodeOptions::frames`].
+///
+/// ```
+/// let buf = [0x02, 0x11, 0x00, 0x05, 0x00, 0x03, 0x22, 0x33, 0x00, 0x02];
+/// let mut frames = corncobs::frames(&buf);
+///
+/// let f = frames.next().unwrap().unwrap();
+/// assert_eq!(f.range, 0..3);
+/// assert_eq!(f.decoded().collect::<Vec<_>>(), [0x11]);
+///
+/// // A damaged frame:
+/// assert!(frames.next().unwrap().is_err());
+///
+/// let f = frames.next().unwrap().unwrap();
+/// assert_eq!(f.range, 5..9);
+/// assert_eq!(f.decoded().collect::<Vec<_>>(), [0x22, 0x33]);
+///
+/// // A frame that's been cut off:
+/// let e = frames.next().unwrap().unwrap_err();
+/// assert_eq!((e.kind, e.offset), (corncobs::CobsError::Truncated, 10));
+/// assert_eq!(frames.next(), None);
+/// ```
+pub fn frames(bytes: &[u8]) -> Frames<'_> {
+    DecodeOptions::new().frames(bytes)
+}
+
+/// Returns an iterator over the encoded frames in `bytes`, decoding each one
+/// in-place as it's found.
+///
+/// This is the in-place equivalent of [`frames`], and follows the same rules
+/// about errors. Each frame is yielded as a [`FrameMut`] giving the decoded
+/// message, which is left in `bytes` at the start of the frame's range.
+/// Damaged frames have their contents scrambled. To check frames more
+/// strictly, use [`DecodeOptions::frames_in_place`].
+///
+/// ```
+/// let mut buf = [0x02, 0x11, 0x00, 0x03, 0x22, 0x33, 0x00];
+/// let decoded: Vec<Vec<u8>> = corncobs::frames_in_place(&mut buf)
+///     .map(|f| f.unwrap().decoded.to_vec())
+///     .collect();
+/// assert_eq!(decoded, [&[0x11][..], &[0x22, 0x33]]);
+/// ```
+pub fn frames_in_place(bytes: &mut [u8]) -> FramesInPlace<'_> {
+    DecodeOptions::new().frames_in_place(bytes)
+}
+
+impl DecodeOptions {
+    /// Returns an iterator over the encoded frames in `bytes`, like
+    /// [`frames`], checking each frame using these options.
+    ///
+    /// ```
+    /// use corncobs::{CobsError, DecodeOptions};
+    ///
+    /// // The second frame is an empty message, sent as a bare terminator.
+    /// let buf = [0x02, 0x11, 0x00, 0x00];
+    /// assert!(corncobs::frames(&buf).all(|f| f.is_ok()));
+    ///
+    /// let canonical = DecodeOptions::new().canonical(true);
+    /// let mut frames = canonical.frames(&buf);
+    /// assert!(frames.next().unwrap().is_ok());
+    /// assert_eq!(frames.next().unwrap().unwrap_err().kind, CobsError::NonCanonical);
+    /// ```
+    pub fn frames<'a>(&self, bytes: &'a [u8]) -> Frames<'a> {
+        Frames {
+            bytes,
+            offset: 0,
+            options: *self,
+        }
+    }
+
+    /// Returns an iterator over the encoded frames in `bytes`, decoding each
+    /// one in-place, like [`frames_in_place`], using these options.
+    pub fn frames_in_place<'a>(&self, bytes: &'a mut [u8]) -> FramesInPlace<'a> {
+        FramesInPlace {
+            rest: bytes,
+            offset: 0,
+            options: *self,
+        }
+    }
+}
+
+/// A checked frame found by [`frames`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// Position of the encoded frame in the buffer, including the terminator.
+    pub range: Range<usize>,
+    /// The encoded frame, including the terminator.
+    pub encoded: &'a [u8],
+    /// Length of the message after decoding.
+    pub decoded_len: usize,
+}
+
+impl<'a> Frame<'a> {
+    /// Decodes the frame into `output`, returning the number of bytes used.
+    ///
+    /// Since the frame has already been checked, the only thing that can go
+    /// wrong here is `output` being shorter than `decoded_len`, which gets you
+    /// `CobsError::OutputTooSmall`.
+    pub fn decode_into(&self, output: &mut [u8]) -> Result<usize, CobsError> {
+        decode_buf(self.encoded, output)
+    }
+
+    /// Decodes the frame a byte at a time, through an iterator. This needs no
+    /// memory to hold the decoded message.
+    pub fn decoded(&self) -> impl Iterator<Item = u8> + 'a {
+        let mut decoder = Decoder::default();
+        // The frame has been checked, and contains exactly one zero at the
+        // very end, so the decoder will neither fail nor be fed past the end.
+        self.encoded
+            .iter()
+            .filter_map(move |&b| match decoder.advance(b) {
+                Ok(DecodeStatus::Append(b)) => Some(b),
+                _ => None,
+            })
+    }
+}
+
+/// A frame decoded by [`frames_in_place`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct FrameMut<'a> {
+    /// Position of the encoded frame in the buffer, including the terminator.
+    /// The decoded message starts at the beginning of this range.
+    pub range: Range<usize>,
+    /// The decoded message.
+    pub decoded: &'a mut [u8],
+}
+
+impl FrameMut<'_> {
+    /// Returns the position of the decoded message in the buffer.
+    pub fn decoded_range(&self) -> Range<usize> {
+        self.range.start..self.range.start + self.decoded.len()
+    }
+}
+
+/// Iterator returned by [`frames`].
+#[derive(Clone, Debug)]
+pub struct Frames<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    options: DecodeOptions,
+}
+
+impl Frames<'_> {
+    /// Returns the position in the buffer where the next frame starts. If the
+    /// iterator has just returned an error, the damaged frame ended here.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Frame<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset;
+        let len = frame_len(&self.bytes[start..])?;
+        let end = start + len;
+        self.offset = end;
+        let encoded = &self.bytes[start..end];
+        let result = self.options.validate_detailed(encoded);
+        Some(
+            result
+                .map(|info| Frame {
+                    range: start..end,
+                    encoded,
+                    decoded_len: info.decoded_len,
+                })
+                .map_err(|e| in_buffer(e, start)),
+        )
+    }
+}
+
+/// Iterator returned by [`frames_in_place`].
+#[derive(Debug)]
+pub struct FramesInPlace<'a> {
+    rest: &'a mut [u8],
+    offset: usize,
+    options: DecodeOptions,
+}
+
+impl FramesInPlace<'_> {
+    /// Returns the position in the buffer where the next frame starts. If the
+    /// iterator has just returned an error, the damaged frame ended here.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for FramesInPlace<'a> {
+    type Item = Result<FrameMut<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = frame_len(self.rest)?;
+        let (frame, rest) = core::mem::take(&mut self.rest).split_at_mut(len);
+        self.rest = rest;
+        let start = self.offset;
+        self.offset += len;
+        let result = self.options.decode_in_place_detailed(frame);
+        Some(
+            result
+                .map(|d| FrameMut {
+                    range: start..start + len,
+                    decoded: &mut frame[..d.decoded_len],
+                })
+                .map_err(|e| in_buffer(e, start)),
+        )
+    }
+}
+
+/// Moves the offset of an error in the frame at `start` to be relative to the
+/// whole buffer.
+fn in_buffer(e: DecodeError, start: usize) -> DecodeError {
+    DecodeError {
+        offset: start + e.offset,
+        ..e
+    }
+}
+
+/// Finds the length of the frame at the start of `bytes`, which is everything
+/// up to and including the first zero, or all of `bytes` if there isn't one.
+/// Returns `None` if `bytes` is empty.
+fn frame_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.is_empty() {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .position(|&b| b == ZERO)
+            .map(|i| i + 1)
+            .unwrap_or(bytes.len()),
+    )
+}
@@ -0,0 +1,424 @@
+//! Adapters between COBS and `std::io`.
+
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    checked_max_encoded_len, CobsError, DecodeError, DecodeOptions, Decoded, Decoder, Encoder, Sink,
+    ZERO,
+};
+
+/// A `std::io::Write` adapter that COBS-encodes everything written to it and
+/// passes the result on to an inner writer.
+///
+/// Bytes written to a `CobsWriter` become the payload of the current frame.
+/// The frame is ended by calling [`CobsWriter::end_frame`], which writes out
+/// the terminator; a new frame then begins. [`CobsWriter::finish`] ends the
+/// frame and gives you the inner writer back.
+///
+/// Encoded data is passed to the inner writer a run at a time, so a
+/// `CobsWriter` needs no more than a run's worth (254 bytes) of buffering,
+/// regardless of the size of the frame.
+///
+/// Because of this buffering, an error from the inner writer usually turns up
+/// after the bytes that caused it have been accepted. So, as with `BufWriter`,
+/// a `write` that accepts bytes always reports all of them written, and an
+/// error from the inner writer is returned by the next call to `write`,
+/// `flush`, [`CobsWriter::end_frame`] or [`CobsWriter::finish`] instead, before
+/// that call does anything else. The frame in progress has probably been
+/// damaged, and you'll want to end it and start over.
+///
+/// ```
+/// use std::io::Write;
+///
+/// let mut w = corncobs::CobsWriter::new(vec![]);
+/// w.write_all(&[0x11, 0x22]).unwrap();
+/// w.write_all(&[0x00, 0x33]).unwrap();
+/// let out = w.finish().unwrap();
+/// assert_eq!(out, [0x03, 0x11, 0x22, 0x02, 0x33, 0x00]);
+/// ```
+#[derive(Debug)]
+pub struct CobsWriter<W: Write> {
+    encoder: Encoder<WriteSink<W>>,
+}
+
+impl<W: Write> CobsWriter<W> {
+    /// Creates a `CobsWriter` that will send encoded frames to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            encoder: Encoder::new(WriteSink { inner, error: None }),
+        }
+    }
+
+    /// Ends the current frame, writing any buffered data and the terminator to
+    /// the inner writer. Subsequent writes start a new frame.
+    ///
+    /// This does not flush the inner writer.
+    ///
+    /// If an earlier write failed, this returns that error without ending the
+    /// frame; call it again to end the frame anyway.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.encoder.sink_mut().take_error()?;
+        self.encoder.end_frame();
+        self.encoder.sink_mut().take_error()
+    }
+
+    /// Ends the current frame (as with [`CobsWriter::end_frame`]) and returns
+    /// the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end_frame()?;
+        Ok(self.encoder.into_sink().inner)
+    }
+
+    /// Gets a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.encoder.sink().inner
+    }
+
+    /// Gets a mutable reference to the inner writer. Writing to it directly in
+    /// the middle of a frame is likely to corrupt the frame.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.encoder.sink_mut().inner
+    }
+}
+
+impl<W: Write> Write for CobsWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Once `buf` is pushed, it's part of the frame, so any error has to be
+        // reported before then, or a caller that retries would repeat it.
+        self.encoder.sink_mut().take_error()?;
+        self.encoder.push(buf);
+        Ok(buf.len())
+    }
+
+    /// Flushes the inner writer. Note that this can't flush data that is
+    /// buffered waiting for the end of its run; for that, you need to end the
+    /// frame.
+    fn flush(&mut self) -> io::Result<()> {
+        let sink = self.encoder.sink_mut();
+        sink.take_error()?;
+        sink.inner.flush()
+    }
+}
+
+/// Glue between the infallible `Sink` interface and a fallible `Write`. The
+/// first error is stashed for the `CobsWriter` to pick up, and output is
+/// dropped until it does.
+#[derive(Debug)]
+struct WriteSink<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W> WriteSink<W> {
+    fn take_error(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Sink for WriteSink<W> {
+    fn accept(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            if let Err(e) = self.inner.write_all(bytes) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// A frame reader that pulls COBS-encoded data from a `std::io::BufRead` and
+/// decodes it a frame at a time.
+///
+/// Frames may arrive in arbitrarily small pieces; the reader keeps going until
+/// it finds a terminator. Decode errors are reported as `io::Error`s of kind
+/// `InvalidData` (or `UnexpectedEof`, if the stream ends in the middle of a
+/// frame). The error inside is a [`DecodeError`], not a bare [`CobsError`]:
+/// downcast to `DecodeError`, and find the `CobsError` in its `kind`. Its
+/// offset is counted from the start of the frame. Because the reader always
+/// consumes input up to the next terminator, it's back in sync with the stream
+/// after an error, and you can just keep reading.
+///
+/// By default, the reader buffers a whole encoded frame, however long it gets
+/// before its terminator turns up. To put a bound on this, see
+/// [`CobsReader::with_max_len`].
+///
+/// A lone zero in the input decodes as an empty frame, as it would with
+/// `decode_buf`. Frames are decoded with `decode_in_place`'s default checks,
+/// unless you ask for others with [`CobsReader::with_options`].
+///
+/// ```
+/// let input: &[u8] = &[0x02, 0x11, 0x00, 0x03, 0x22, 0x33, 0x00];
+/// let mut r = corncobs::CobsReader::new(input);
+/// let mut frame = vec![];
+///
+/// assert_eq!(r.read_frame(&mut frame).unwrap(), Some(1));
+/// assert_eq!(frame, [0x11]);
+///
+/// frame.clear();
+/// assert_eq!(r.read_frame(&mut frame).unwrap(), Some(2));
+/// assert_eq!(frame, [0x22, 0x33]);
+///
+/// assert_eq!(r.read_frame(&mut frame).unwrap(), None);
+/// ```
+///
+/// Getting at the details of an error:
+///
+/// ```
+/// use corncobs::{CobsError, CobsReader, DecodeError};
+///
+/// // The run is cut short by the terminator.
+/// let input: &[u8] = &[0x04, 0x11, 0x00];
+/// let e = CobsReader::new(input).read_frame(&mut vec![]).unwrap_err();
+/// assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+/// let e = e.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+/// assert_eq!(e.kind, CobsError::Truncated);
+/// ```
+#[derive(Debug)]
+pub struct CobsReader<R: BufRead> {
+    inner: R,
+    options: DecodeOptions,
+    /// Limit on the decoded length of a frame, if any.
+    max_len: Option<usize>,
+    /// Whether we're dropping the rest of a frame that was too long.
+    hunting: bool,
+}
+
+impl<R: BufRead> CobsReader<R> {
+    /// Creates a `CobsReader` that will read encoded frames from `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            options: DecodeOptions::new(),
+            max_len: None,
+            hunting: false,
+        }
+    }
+
+    /// Decodes frames using `options`, such as to reject non-canonical
+    /// encodings.
+    ///
+    /// ```
+    /// use corncobs::{CobsReader, DecodeOptions};
+    ///
+    /// // An empty message, sent as a bare terminator.
+    /// let input: &[u8] = &[0x00];
+    /// let mut r = CobsReader::new(input).with_options(DecodeOptions::new().canonical(true));
+    /// assert!(r.read_frame(&mut vec![]).is_err());
+    /// ```
+    pub fn with_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets a limit on the decoded length of a frame.
+    ///
+    /// Without a limit, a stuck or noisy line that never sends a zero makes
+    /// the reader buffer input forever. With one, the reader gives up on a
+    /// frame as soon as its encoded form is too long to decode to `max_len`
+    /// bytes or less, and reports `CobsError::TooLong` (in a `DecodeError`,
+    /// as an `io::Error` of kind `InvalidData`) without waiting for the
+    /// terminator. It then
+    /// resynchronizes, like a [`Decoder`](crate::Decoder) using
+    /// `with_resync(true).with_max_len(max_len)`: the next read drops input up
+    /// to the next zero, and then reads the frame after it. If the line is
+    /// still stuck, that read reports `TooLong` again, after dropping as many
+    /// bytes as a frame could have taken, so the reader never holds more than
+    /// about [`max_encoded_len(max_len)`](crate::max_encoded_len) bytes of
+    /// input.
+    ///
+    /// The error says where the frame got too long, as a `Decoder` would.
+    /// When the reader is still dropping the rest of an earlier frame, there's
+    /// nothing to go on, so the offset is just the number of bytes dropped.
+    ///
+    /// ```
+    /// use corncobs::{CobsError, CobsReader, DecodeError};
+    ///
+    /// let input: &[u8] = &[0x05, 0x11, 0x22, 0x33, 0x44, 0x00, 0x02, 0x55, 0x00];
+    /// let mut r = CobsReader::new(input).with_max_len(2);
+    /// let mut frame = vec![];
+    ///
+    /// let e = r.read_frame(&mut frame).unwrap_err();
+    /// let e = e.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+    /// assert_eq!((e.kind, e.offset), (CobsError::TooLong, 3));
+    /// assert_eq!(r.read_frame(&mut frame).unwrap(), Some(1));
+    /// assert_eq!(frame, [0x55]);
+    /// ```
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Reads the next frame, decodes it, and appends the result to `buf`.
+    ///
+    /// Returns `Ok(Some(n))` with the length of the decoded frame, or
+    /// `Ok(None)` if the inner reader is at end-of-file.
+    ///
+    /// If this returns an error, the contents of `buf` past its original length
+    /// are unspecified.
+    pub fn read_frame(&mut self, buf: &mut Vec<u8>) -> io::Result<Option<usize>> {
+        // We read the encoded frame into `buf` and decode it in place, which
+        // saves us keeping a second buffer around.
+        let offset = buf.len();
+        let n = match self.max_len {
+            None => self.inner.read_until(ZERO, buf)?,
+            Some(max_len) => self.read_until_bounded(buf, max_len)?,
+        };
+        if n == 0 {
+            return Ok(None);
+        }
+        // If there's no terminator, we've hit the end of the input in the
+        // middle of a frame, and decoding will tell us where it was cut off.
+        let kind = if buf.last() == Some(&ZERO) {
+            io::ErrorKind::InvalidData
+        } else {
+            io::ErrorKind::UnexpectedEof
+        };
+        if let Some(max_len) = self.max_len {
+            // Frames that fit in `limit` can still decode to a bit more than
+            // `max_len`, since runs of zeroes encode more compactly than the
+            // worst case. They have to be longer than `max_len` to start with.
+            // (`n` is at least 1, and `max_len + 1` could overflow.)
+            if kind == io::ErrorKind::InvalidData && n - 1 > max_len {
+                if let Some(e) = too_long(&buf[offset..], max_len) {
+                    buf.truncate(offset);
+                    return Err(io::Error::new(kind, e));
+                }
+            }
+        }
+        let result = self.options.decode_in_place_detailed(&mut buf[offset..]);
+        match result {
+            Ok(Decoded { decoded_len, .. }) => {
+                buf.truncate(offset + decoded_len);
+                Ok(Some(decoded_len))
+            }
+            Err(e) => {
+                buf.truncate(offset);
+                Err(io::Error::new(kind, e))
+            }
+        }
+    }
+
+    /// Reads input into `buf` up to and including the next zero, like
+    /// `read_until`, but gives up with `CobsError::TooLong` after reading as
+    /// much as a frame of `max_len` bytes could take without finding one, and
+    /// drops the rest of that frame on the next call. Returns the number of
+    /// bytes appended to `buf`.
+    fn read_until_bounded(&mut self, buf: &mut Vec<u8>, max_len: usize) -> io::Result<usize> {
+        let limit = checked_max_encoded_len(max_len).unwrap_or(usize::MAX);
+        let start = buf.len();
+        let mut n = 0;
+        loop {
+            let available = match self.inner.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                // End of file. Whatever we were hunting through is gone, and
+                // a partial frame is for our caller to report.
+                if self.hunting {
+                    self.hunting = false;
+                    return Ok(0);
+                }
+                return Ok(n);
+            }
+            let room = limit - n;
+            let (used, found) = match available.iter().position(|&b| b == ZERO) {
+                Some(i) if i < room => (i + 1, true),
+                _ => (usize::min(available.len(), room), false),
+            };
+            if !self.hunting {
+                buf.extend_from_slice(&available[..used]);
+            }
+            self.inner.consume(used);
+            n += used;
+            if found {
+                if !self.hunting {
+                    return Ok(n);
+                }
+                // Back in sync; the next frame starts here.
+                self.hunting = false;
+                n = 0;
+            } else if n == limit {
+                let e = if self.hunting {
+                    None
+                } else {
+                    too_long(&buf[start..], max_len)
+                };
+                let e = e.unwrap_or(DecodeError {
+                    kind: CobsError::TooLong,
+                    offset: n,
+                    code: None,
+                    output_len: 0,
+                });
+                buf.truncate(start);
+                self.hunting = true;
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+        }
+    }
+
+    /// Returns an iterator over the decoded frames in the input, as `Vec`s.
+    ///
+    /// The iterator ends at end-of-file. Errors (including decode errors) are
+    /// yielded as they happen, and iteration can continue past them.
+    pub fn frames(self) -> ReadFrames<R> {
+        ReadFrames { reader: self }
+    }
+
+    /// Gets a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner reader. Reading from it directly
+    /// is likely to lose sync with the frames in the stream, but the next
+    /// frame after that will be found correctly.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Checks whether the frame `encoded` (which may be incomplete) decodes to more
+/// than `max_len` bytes, and if so, describes where, as a `Decoder` would.
+fn too_long(encoded: &[u8], max_len: usize) -> Option<DecodeError> {
+    let mut decoder = Decoder::new().with_max_len(max_len);
+    let mut scratch = [0; 64];
+    let mut input = encoded;
+    while !input.is_empty() {
+        match decoder.feed(input, &mut scratch) {
+            Ok(r) if r.frame_done => break,
+            Ok(r) => input = &input[r.consumed..],
+            Err(e) if e.error == CobsError::TooLong => return Some(e.into()),
+            // Anything else is for decoding to report, using our options.
+            Err(_) => break,
+        }
+    }
+    None
+}
+
+/// Iterator over decoded frames, produced by [`CobsReader::frames`].
+#[derive(Debug)]
+pub struct ReadFrames<R: BufRead> {
+    reader: CobsReader<R>,
+}
+
+impl<R: BufRead> Iterator for ReadFrames<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut frame = vec![];
+        match self.reader.read_frame(&mut frame) {
+            Ok(Some(_)) => Some(Ok(frame)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -9,7 +9,8 @@
 //! 
 //! - Provides a `const fn` for computing the maximum encoded size for a given
 //! input size, so you can define fixed-size buffers precisely without magic
-//! numbers.
+//! numbers. (And some other sizing functions, like [`encoded_len`] and
+//! [`max_decoded_len`], for when you need more detail.)
 //! 
 //! - Has pretty good test coverage, [Criterion] benchmarks, and a [honggfuzz]
 //! fuzz testing suite to try to ensure code quality.
@@ -21,7 +22,8 @@
 //! the very end. `corncobs` implements the version of this where the byte is
 //! zero.  That is, `corncobs` can take a sequence of arbitrary bytes, and turn
 //! it into a slightly longer sequence that doesn't contain zero except at the
-//! end.
+//! end. (It can use some other byte instead, if you need it to; see [`Cobs`].
+//! And it can work on wider units than bytes, such as `u16`; see [`symbol`].)
 //! 
 //! The main reason you'd want to do this is _framing._ If you're transmitting a
 //! series of messages over a stream, you need some way to tell where the
@@ -51,11 +53,43 @@
 //!   - [`encode_iter`]: incremental, using an iterator; somewhat slower, but
 //!   requires no additional memory. (This can be useful in a serial interrupt
 //!   handler.)
+//!   - [`try_encode_buf`] and [`EncodeCursor`]: like `encode_buf`, but
+//!   reporting an error, or encoding in pieces, when the output is too small.
+//!   - [`encode_in_place`]: in-place in a slice, with some headroom reserved
+//!   in front of the message ([`FrameBuf`] helps with this).
+//!   - [`encode_vectored_buf`] and [`encode_vectored_iter`]: like the above,
+//!   but taking the message as several slices, without copying them into one
+//!   buffer first.
+//!   - [`encode_segments`]: zero-copy, producing a list of slices to be
+//!   written out in order; good for DMA scatter lists and vectored I/O.
+//!   - [`Encoder`]: incremental, accepting the message in pieces; buffers at
+//!   most one run (254 bytes), and can be fed as the message is produced.
+//!   - [`rcobs`]: a variant of COBS that can be encoded without buffering
+//!   or looking ahead at all, at the cost of decoding backwards.
 //! - Decoding
 //!   - [`decode_buf`]: from one slice to another; efficient, but requires 2x
 //!   the available RAM.
 //!   - [`decode_in_place`]: in-place in a slice; nearly as efficient, but
 //!   overwrites incoming data.
+//!   - [`decode_buf_with_consumed`] and [`decode_in_place_with_consumed`]:
+//!   like the above, but also report where the message ended, for walking
+//!   through several messages in one buffer.
+//!   - [`decode_ring_buf`]: from a circular buffer (as two slices) to
+//!   another slice, for messages that wrap around the end of the buffer.
+//!   - [`DecodeOptions`]: the above, with extra checks (strict validation,
+//!   or rejecting non-canonical encodings) turned on.
+//!   - [`validate`]: checks a message and measures its decoded size, without
+//!   decoding it.
+//!   - [`DecodeOptions::decode_buf_detailed`]: reports where in a message
+//!   decoding failed, for diagnosing damaged messages.
+//!   - [`is_canonical`]: checks whether a message is encoded exactly the way
+//!   `encode_buf` would have done it.
+//!   - [`frames`] and [`frames_in_place`]: iterate over all the messages in a
+//!   buffer, skipping past any damaged ones.
+//!   - [`Decoder`]: incremental, a byte or a chunk at a time, for streams; can
+//!   optionally resynchronize itself after errors.
+//!   - [`FrameReceiver`]: incremental, collecting a stream into whole messages
+//!   in a fixed-size buffer.
 //!
 //! ## Design decisions / tradeoffs
 //!
@@ -88,6 +122,11 @@
 //! integrity check), or it will return an `Err`. It will not crash, corrupt
 //! memory, or `panic!`, and we have tests to demonstrate this.
 //!
+//! If you'd like validation anyway -- say, because you're forwarding messages
+//! without checking them yourself -- you can turn it on with
+//! [`DecodeOptions::strict`], at some cost in speed. The [`Decoder`] always
+//! validates, since it looks at every byte regardless.
+//!
 //! ## Cargo `features`
 //! 
 //! No features are enabled by default. Embedded programmers do not need to
@@ -98,8 +137,11 @@
 //! 
 //! - `std`: if you're on one of them "big computers" with "infinite memory" and
 //! can afford the inherent nondeterminism of dynamic memory allocation, this
-//! feature enables routines for encoding to-from `Vec`, and an `Error` impl for
-//! `CobsError`.
+//! feature enables routines for encoding to-from `Vec`, and `std::io`
+//! adapters ([`CobsWriter`] and [`CobsReader`]).
+//!
+//! The error types implement `core::error::Error` with or without `std`, which
+//! needs Rust 1.81 or later.
 //! 
 //! ## Tips for using COBS
 //! 
@@ -108,7 +150,10 @@
 //! 
 //! **Optimizing for size:** COBS encoding has the least overhead when the data
 //! being encoded contains `0x00` bytes, at least one for every 254 bytes sent.
-//! In practice, most data formats achieve this. However...
+//! In practice, most data formats achieve this. If your messages are short,
+//! the COBS/R variant in [`cobsr`] can often shave off another byte. If they
+//! contain long stretches of zeroes, the COBS/ZPE variant in [`zpe`] encodes
+//! those in half the space. However...
 //! 
 //! **Optimizing for speed:** COBS encode/decode, and particularly the
 //! `corncobs` implementation, goes fastest when data contains as _few_ `0x00`
@@ -133,12 +178,40 @@
 // crates you depend on, including this one.
 #![forbid(unsafe_code)]
 
+// The docs in this crate don't indent the continuation lines of list items.
+#![allow(clippy::doc_lazy_continuation)]
+// The size bounds spell out their rounding, as they always have, rather than
+// using `div_ceil`.
+#![allow(clippy::manual_div_ceil)]
+
+pub mod cobsr;
+mod delim;
+mod frames;
+#[cfg(feature = "std")]
+mod io;
+pub mod rcobs;
+mod receiver;
+pub mod symbol;
+pub mod zpe;
+
+pub use delim::Cobs;
+pub use frames::{frames, frames_in_place, Frame, FrameMut, Frames, FramesInPlace};
+pub use receiver::{FrameReceiver, FrameReceiverRef};
+
+#[cfg(feature = "std")]
+pub use io::{CobsReader, CobsWriter, ReadFrames};
+
+use core::marker::PhantomData;
+
+use symbol::Symbol;
+
 /// The termination byte used by `corncobs`. Yes, it's a bit silly to have this
 /// as a constant -- but the implementation is careful to use this named
 /// constant whenever it is talking about the termination byte, for clarity.
 ///
-/// The value of this (`0`) is assumed by the implementation and can't easily be
-/// changed.
+/// The value of this (`0`) is assumed by most of the implementation and can't
+/// easily be changed. If you need a different delimiter, see [`Cobs`], which
+/// offers the main encode and decode routines for any delimiter.
 pub const ZERO: u8 = 0;
 
 /// Longest run of unchanged bytes that can be encoded using COBS.
@@ -174,6 +247,95 @@ pub const fn max_encoded_len(raw_len: usize) -> usize {
     raw_len + overhead + 1
 }
 
+/// Returns the largest possible encoded size for an input message of `raw_len`
+/// bytes, like [`max_encoded_len`], or `None` if that would overflow a
+/// `usize`.
+///
+/// `max_encoded_len` can't be used with lengths near `usize::MAX`, where it
+/// would either fail to compile (in a `const`) or panic or wrap (at runtime).
+/// If you're computing sizes from untrusted numbers, use this instead.
+///
+/// ```
+/// assert_eq!(corncobs::checked_max_encoded_len(254), Some(256));
+/// assert_eq!(corncobs::checked_max_encoded_len(usize::MAX), None);
+/// ```
+pub const fn checked_max_encoded_len(raw_len: usize) -> Option<usize> {
+    let overhead = if raw_len == 0 {
+        1
+    } else {
+        // Like `max_encoded_len`, but without overflowing on the way.
+        (raw_len - 1) / 254 + 1
+    };
+    match raw_len.checked_add(overhead) {
+        Some(n) => n.checked_add(1),
+        None => None,
+    }
+}
+
+/// Returns the smallest possible encoded size for an input message of
+/// `raw_len` bytes.
+///
+/// Every message takes at least one length byte and a terminator, so this is
+/// `raw_len + 2`. Messages that contain at least one zero in every 254 bytes
+/// encode to exactly this size.
+pub const fn min_encoded_len(raw_len: usize) -> usize {
+    raw_len + 2
+}
+
+/// Returns the smallest possible encoded size for an input message of
+/// `raw_len` bytes, like [`min_encoded_len`], or `None` if that would overflow
+/// a `usize`.
+pub const fn checked_min_encoded_len(raw_len: usize) -> Option<usize> {
+    raw_len.checked_add(2)
+}
+
+/// Returns the largest size that an encoded message of `encoded_len` bytes
+/// (including the terminator) can decode to.
+///
+/// This is a `const fn`, so you can use it to size receive buffers from the
+/// largest encoded message your link can carry:
+///
+/// ```
+/// const MTU: usize = 64;
+/// let mut decoded = [0; corncobs::max_decoded_len(MTU)];
+/// # assert_eq!(decoded.len(), 62);
+/// ```
+///
+/// This can't overflow, so there's no need for a checked version.
+pub const fn max_decoded_len(encoded_len: usize) -> usize {
+    // Every message has at least one length byte and a terminator.
+    encoded_len.saturating_sub(2)
+}
+
+/// Returns the exact number of bytes that `encode_buf` will produce for
+/// `bytes`, including the terminator.
+///
+/// This has to look at the whole message, so it takes time proportional to its
+/// length (though it's cheaper than encoding it). If you only need a bound,
+/// see [`max_encoded_len`] and [`min_encoded_len`].
+///
+/// ```
+/// let msg = [0x11, 0x00, 0x22];
+/// assert_eq!(corncobs::encoded_len(&msg), 5);
+/// assert_eq!(corncobs::encoded_len(&msg), corncobs::min_encoded_len(msg.len()));
+/// ```
+pub fn encoded_len(bytes: &[u8]) -> usize {
+    encoded_len_core(bytes)
+}
+
+/// Implementation of `encoded_len`, for any symbol type.
+fn encoded_len_core<S: Symbol>(bytes: &[S]) -> usize {
+    // +1 for terminator byte.
+    let mut len = 1;
+    let mut rest = Some(bytes);
+    while let Some(bytes) = rest {
+        let (run, new_rest) = take_run_delim::<S, ZERO>(bytes);
+        len += 1 + run.len();
+        rest = new_rest;
+    }
+    len
+}
+
 /// Encodes the message `bytes` into the buffer `output`. Returns the number of
 /// bytes used in `output`, which also happens to be the index of the first zero
 /// byte.
@@ -186,10 +348,17 @@ pub const fn max_encoded_len(raw_len: usize) -> usize {
 /// # Panics
 ///
 /// If `output` is too small to contain the encoded form of `input`.
-pub fn encode_buf(bytes: &[u8], mut output: &mut [u8]) -> usize {
+pub fn encode_buf(bytes: &[u8], output: &mut [u8]) -> usize {
+    encode_buf_core::<u8, ZERO>(bytes, output)
+}
+
+/// Implementation of `encode_buf`, for any symbol type, using `DELIM` as the
+/// delimiter. (See [`Cobs`] for how that works.)
+fn encode_buf_core<S: Symbol, const DELIM: u8>(bytes: &[S], mut output: &mut [S]) -> usize {
     // We'll panic if the precondition is violated regardless, but this makes
     // the error a bit easier to spot in tests:
-    debug_assert!(output.len() >= max_encoded_len(bytes.len()));
+    debug_assert!(output.len() >= symbol::max_encoded_len::<S>(bytes.len()));
+    let delim = S::from(DELIM);
 
     // Capture the original size of the output, because we're going to shorten
     // it as we write bytes.
@@ -202,12 +371,12 @@ pub fn encode_buf(bytes: &[u8], mut output: &mut [u8]) -> usize {
     //
     // Currently, the scanning-for-zeros loop here is the hottest part of the
     // encode profile.
-    for mut run in bytes.split(|&b| b == ZERO) {
+    for mut run in bytes.split(|&b| b == delim) {
         // If the last run we encoded was maximal length, we need to encode an
         // explicit zero between it and our current `run`.
         if prev_run_was_maximal {
             let (chunk, new_output) = output.split_at_mut(1);
-            chunk[0] = encode_len(0);
+            chunk[0] = S::encode_len(0) ^ delim;
             output = new_output;
         }
 
@@ -216,15 +385,15 @@ pub fn encode_buf(bytes: &[u8], mut output: &mut [u8]) -> usize {
         // extreme case, if the input contains no zeroes, we'll process all of
         // it here.
         loop {
-            let chunk_len = usize::min(run.len(), MAX_RUN);
+            let chunk_len = usize::min(run.len(), S::MAX_RUN);
             let (chunk, new_output) = output.split_at_mut(chunk_len + 1);
             let (run_prefix, new_run) = run.split_at(chunk_len);
             chunk[1..].copy_from_slice(run_prefix);
-            chunk[0] = encode_len(chunk_len);
+            chunk[0] = S::encode_len(chunk_len) ^ delim;
 
             output = new_output;
             run = new_run;
-            prev_run_was_maximal = chunk_len == MAX_RUN;
+            prev_run_was_maximal = chunk_len == S::MAX_RUN;
 
             // We test this condition here, rather than as a `while` loop,
             // because we want to process empty runs once.
@@ -235,7 +404,7 @@ pub fn encode_buf(bytes: &[u8], mut output: &mut [u8]) -> usize {
     }
     // We've been shortening the output as we go by lopping off prefixes, so our
     // terminating byte goes at the new start:
-    output[0] = 0;
+    output[0] = delim;
     orig_size - (output.len() - 1)
 }
 
@@ -252,6 +421,149 @@ pub fn encode(bytes: &[u8], output: &mut Vec<u8>) {
     output.truncate(offset + actual_len);
 }
 
+/// Encodes a message in-place. This is useful when you're short on memory,
+/// since it avoids keeping a second copy of the message around.
+///
+/// The message must occupy the last `payload_len` bytes of `buf`, and `buf`
+/// must be at least `max_encoded_len(payload_len)` bytes long. The bytes
+/// before the message are headroom, used to make space for the overhead added
+/// by encoding; their initial contents don't matter.
+///
+/// The encoded message is deposited into `buf` starting at index 0, and
+/// `encode_in_place` returns its length. Bytes after that are left with
+/// unspecified contents.
+///
+/// If you'd rather not do the headroom math yourself, see [`FrameBuf`].
+///
+/// ```
+/// let mut buf = [0; corncobs::max_encoded_len(4)];
+/// let payload_start = buf.len() - 4;
+/// buf[payload_start..].copy_from_slice(&[0x11, 0x22, 0x00, 0x33]);
+///
+/// let len = corncobs::encode_in_place(&mut buf, 4);
+/// assert_eq!(&buf[..len], [0x03, 0x11, 0x22, 0x02, 0x33, 0x00]);
+/// ```
+///
+/// # Panics
+///
+/// If `buf` is shorter than `max_encoded_len(payload_len)`.
+pub fn encode_in_place(buf: &mut [u8], payload_len: usize) -> usize {
+    encode_in_place_core::<ZERO>(buf, payload_len)
+}
+
+/// Implementation of `encode_in_place`, using `DELIM` as the delimiter.
+fn encode_in_place_core<const DELIM: u8>(buf: &mut [u8], payload_len: usize) -> usize {
+    // Unlike most of our preconditions, this one is checked in release builds:
+    // violating it wouldn't cause an out-of-bounds access, it would quietly
+    // scribble over the message before we read it.
+    assert!(buf.len() >= max_encoded_len(payload_len));
+
+    // We write output starting at `outpos`, while reading input starting at
+    // `inpos`. Each run costs at most one byte more than it consumes, and the
+    // headroom has been sized so that `outpos` stays behind `inpos` until the
+    // input is gone.
+    let end = buf.len();
+    let mut inpos = end - payload_len;
+    let mut outpos = 0;
+    loop {
+        let max_len = usize::min(end - inpos, MAX_RUN);
+        let run_len = buf[inpos..inpos + max_len]
+            .iter()
+            .position(|&b| b == DELIM)
+            .unwrap_or(max_len);
+        // Move the run first, since this may overlap the spot where its
+        // length byte goes.
+        buf.copy_within(inpos..inpos + run_len, outpos + 1);
+        buf[outpos] = encode_len(run_len) ^ DELIM;
+        outpos += 1 + run_len;
+        inpos += run_len;
+
+        // This follows the same rules as `take_run`.
+        if inpos == end {
+            break;
+        }
+        if run_len != MAX_RUN {
+            // Drop the zero.
+            inpos += 1;
+        }
+    }
+    buf[outpos] = DELIM;
+    outpos + 1
+}
+
+/// A buffer for building a message and then encoding it in-place.
+///
+/// A `FrameBuf` wraps a byte slice and divides it into headroom and a payload
+/// region, such that any message that fits in the payload region can be
+/// encoded in-place with [`FrameBuf::encode`]. This lets you serialize a
+/// message directly into the buffer you'll transmit it from.
+///
+/// ```
+/// const MTU: usize = 300;
+/// let mut storage = [0; corncobs::max_encoded_len(MTU)];
+/// let mut frame = corncobs::FrameBuf::new(&mut storage);
+/// assert_eq!(frame.capacity(), MTU);
+///
+/// frame.payload_mut()[..3].copy_from_slice(b"hi!");
+/// assert_eq!(frame.encode(3), [0x04, b'h', b'i', b'!', 0x00]);
+/// ```
+#[derive(Debug)]
+pub struct FrameBuf<'a> {
+    buf: &'a mut [u8],
+    /// Offset of the payload region in `buf`.
+    headroom: usize,
+}
+
+impl<'a> FrameBuf<'a> {
+    /// Creates a `FrameBuf` using `buf` as storage. The payload capacity will
+    /// be the largest message size that can be encoded in `buf.len()` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is too small to hold even an empty encoded message (2 bytes).
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        assert!(buf.len() >= max_encoded_len(0));
+        // Everything but the terminator is shared between data and overhead,
+        // at a worst-case ratio of 254:1.
+        let shared = buf.len() - 1;
+        let capacity = shared - (shared + MAX_RUN) / (MAX_RUN + 1);
+        Self {
+            headroom: buf.len() - capacity,
+            buf,
+        }
+    }
+
+    /// Returns the size of the payload region, which is the largest message
+    /// this `FrameBuf` can encode.
+    pub fn capacity(&self) -> usize {
+        self.buf.len() - self.headroom
+    }
+
+    /// Returns the payload region. Write the message here, starting at index
+    /// 0, and then call [`FrameBuf::encode`].
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.headroom..]
+    }
+
+    /// Encodes the first `payload_len` bytes of the payload region in-place,
+    /// and returns the encoded message.
+    ///
+    /// This overwrites the payload region, so you'll need to write a new
+    /// message before calling this again.
+    ///
+    /// # Panics
+    ///
+    /// If `payload_len` is greater than the capacity.
+    pub fn encode(&mut self, payload_len: usize) -> &[u8] {
+        assert!(payload_len <= self.capacity());
+        // `encode_in_place` wants the message at the end of the buffer, so
+        // give it a buffer that ends where the message does.
+        let buf = &mut self.buf[..self.headroom + payload_len];
+        let n = encode_in_place(buf, payload_len);
+        &self.buf[..n]
+    }
+}
+
 /// Encoding a len (between `0` and `MAX_RUN` inclusive) into a byte such that
 /// we avoid `ZERO`.
 #[inline(always)]
@@ -278,7 +590,12 @@ fn encode_len(len: usize) -> u8 {
 /// restricted by the speed of a link that gets fed one byte a time, such as a
 /// serial peripheral, this can encode messages with no additional memory.
 pub fn encode_iter(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
-    let mut state = Some(EncodeState::Begin(bytes));
+    encode_iter_core::<ZERO>(bytes)
+}
+
+/// Implementation of `encode_iter`, using `DELIM` as the delimiter.
+fn encode_iter_core<const DELIM: u8>(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let mut state = Some(EncodeState::<DELIM>::Begin(bytes));
     core::iter::from_fn(move || {
         let s = state?;
         let (b, s2) = s.next();
@@ -287,9 +604,9 @@ pub fn encode_iter(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
     })
 }
 
-/// State for incremental encoding.
+/// State for incremental encoding, using `DELIM` as the delimiter.
 #[derive(Copy, Clone, Debug)]
-enum EncodeState<'a> {
+enum EncodeState<'a, const DELIM: u8> {
     /// We are at a run boundary and need to determine the size of the next run
     /// and emit an overhead byte.
     ///
@@ -322,18 +639,18 @@ enum EncodeState<'a> {
     End,
 }
 
-impl<'a> EncodeState<'a> {
+impl<'a, const DELIM: u8> EncodeState<'a, DELIM> {
     pub fn next(self) -> (u8, Option<Self>) {
         match self {
             Self::Begin(bytes) => {
-                let (run, rest) = take_run(bytes);
-                let b = encode_len(run.len());
+                let (run, rest) = take_run_delim::<u8, DELIM>(bytes);
+                let b = encode_len(run.len()) ^ DELIM;
                 (b, Some(Self::next_run_state(run, rest)))
             }
             Self::Run(b, run, rest) => {
                 (b, Some(Self::next_run_state(run, rest)))
             }
-            Self::End => (0, None),
+            Self::End => (DELIM, None),
         }
     }
 
@@ -369,217 +686,1824 @@ impl<'a> EncodeState<'a> {
 /// Note that `stuff` may be empty, if `bytes` ends in a `ZERO`. It is still
 /// important to process `stuff` in that case.
 fn take_run(bytes: &[u8]) -> (&[u8], Option<&[u8]>) {
+    take_run_delim::<u8, ZERO>(bytes)
+}
+
+/// Takes a run off the front of `bytes`, like `take_run`, but with `DELIM` in
+/// place of `ZERO`.
+fn take_run_delim<S: Symbol, const DELIM: u8>(bytes: &[S]) -> (&[S], Option<&[S]>) {
+    let delim = S::from(DELIM);
     // The run will be no longer than
     // - All the bytes, or
     // - The fixed MAX_RUN constant.
-    let max_len = usize::min(bytes.len(), MAX_RUN);
+    let max_len = usize::min(bytes.len(), S::MAX_RUN);
     // It may be shorter than that if there's a zero. Scan the prefix for a zero
     // and truncate if found.
     let run_len = bytes.iter()
         .take(max_len)
-        .position(|&b| b == ZERO)
+        .position(|&b| b == delim)
         .unwrap_or(max_len);
 
     let (run, rest) = bytes.split_at(run_len);
     let rest = if rest.is_empty() {
         None
-    } else if run_len == MAX_RUN {
+    } else if run_len == S::MAX_RUN {
         // Run does not imply a zero, don't omit one from the output if present.
         Some(rest)
     } else {
-        debug_assert_eq!(rest[0], 0);
+        debug_assert_eq!(rest[0], delim);
         // Drop the zero.
         Some(&rest[1..])
     };
     (run, rest)
 }
 
-/// Decodes `bytes` into a vector.
+/// Encodes the concatenation of the slices in `parts` into the buffer
+/// `output`, as though they were one message. Returns the number of bytes used
+/// in `output`.
 ///
-/// This is a convenience for cases where you have `std` available. Its behavior
-/// is otherwise identical to `decode_buf`.
-#[cfg(feature = "std")]
-pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
-    let offset = output.len();
-    output.resize(offset + bytes.len(), 0);
-    let actual_len = decode_buf(bytes, &mut output[offset..])?;
-    output.truncate(offset + actual_len);
-    Ok(())
-}
-
-/// Decodes input from `bytes` into `output` starting at index 0. Returns the
-/// number of bytes used in `output`.
+/// This lets you encode a message that's scattered across memory -- say, a
+/// header, a payload, and a checksum -- without first copying it into a
+/// contiguous buffer. Runs are allowed to cross the boundaries between parts,
+/// so the output is identical to `encode_buf` on the concatenated message.
+///
+/// `output` must be large enough to receive the encoded form, which is
+/// `max_encoded_len(n)` worst-case, where `n` is the total length of the
+/// parts.
+///
+/// ```
+/// let header = [0x11, 0x22];
+/// let payload = [0x00, 0x33];
+/// let mut encoded = [0; corncobs::max_encoded_len(4)];
+/// let len = corncobs::encode_vectored_buf(&[&header, &payload], &mut encoded);
+/// assert_eq!(&encoded[..len], [0x03, 0x11, 0x22, 0x02, 0x33, 0x00]);
+/// ```
 ///
 /// # Panics
 ///
-/// If `output` is not long enough to receive the decoded output. To be safe,
-/// `output` must be at least `max_encoded_len(bytes.len())`.
-pub fn decode_buf(mut bytes: &[u8], mut output: &mut [u8]) -> Result<usize, CobsError> {
-    let orig_len = output.len();
-
-    let mut trailing_zero = false;
-    // This while-loop is equivalent to `for b in bytes` except that it lets us
-    // _also_ consume bytes inside the body, which we totally do.
-    while let Some((&head, rest)) = bytes.split_first() {
-        bytes = rest;
-        // Detect message terminator.
-        let n = if let Some(n) = decode_len(head) {
-            n
-        } else {
-            let decoded_len = orig_len - output.len();
-            return Ok(decoded_len);
-        };
-        // If we're not at the end of the message, and our last run was less
-        // than MAX_RUN bytes, we need to insert a zero.
-        if trailing_zero {
-            let (z, new_output) = output.split_at_mut(1);
-            z[0] = ZERO;
-            output = new_output;
-        }
+/// If `output` is too small to contain the encoded form of the parts.
+pub fn encode_vectored_buf(parts: &[&[u8]], output: &mut [u8]) -> usize {
+    debug_assert!(
+        output.len() >= max_encoded_len(parts.iter().map(|p| p.len()).sum())
+    );
 
-        // Skip a bunch of work if our run length is zero. This cuts the
-        // worst-case time in benchmarks (on Intel) by about 50% while only
-        // slightly impacting the less pathological cases.
-        if n != 0 {
-            // Refuse to proceed if the run claims to contain more bytes than
-            // the slice does. (This check prevents a panic in decoding
-            // truncated data.)
-            if bytes.len() < n {
-                break;
-            }
+    // Unlike `encode_buf`, we can't see a whole run at once, since it may be
+    // split across parts. So we reserve a spot for the length byte of the
+    // current run, and fill it in when we find the end of the run.
+    let mut code_pos = 0;
+    let mut out_pos = 1;
+    let mut prev_run_was_maximal = false;
 
-            // Split the remaining data into the block belonging to this run and
-            // allll the rest.
-            let (block, rest) = bytes.split_at(n);
+    for &part in parts {
+        let mut bytes = part;
+        while !bytes.is_empty() {
+            let run_len = out_pos - code_pos - 1;
+            let max_len = usize::min(bytes.len(), MAX_RUN - run_len);
+            let n = bytes[..max_len]
+                .iter()
+                .position(|&b| b == ZERO)
+                .unwrap_or(max_len);
+            let (run, rest) = bytes.split_at(n);
+            output[out_pos..out_pos + n].copy_from_slice(run);
+            out_pos += n;
             bytes = rest;
 
-            // Blit that block!
-            let (block_out, new_output) = output.split_at_mut(block.len());
-            block_out.copy_from_slice(block);
-            output = new_output;
+            let run_len = run_len + n;
+            if run_len == MAX_RUN {
+                output[code_pos] = encode_len(run_len);
+                code_pos = out_pos;
+                out_pos += 1;
+                prev_run_was_maximal = true;
+            } else if let Some((_zero, rest)) = bytes.split_first() {
+                // We stopped short of both the end of the part and the max
+                // run length, so this must be a zero.
+                debug_assert_eq!(_zero, &ZERO);
+                output[code_pos] = encode_len(run_len);
+                code_pos = out_pos;
+                out_pos += 1;
+                prev_run_was_maximal = false;
+                bytes = rest;
+            }
         }
+    }
 
-        // Record whether this run was shorter than the max. Runs shorter than
-        // the max in the middle of a message are always ended by zero, which we
-        // need to insert in the output. However, a shorter-than-max run at the
-        // very _end_ is not terminated by zero, and we handle it above.
-        trailing_zero = n != MAX_RUN;
+    let run_len = out_pos - code_pos - 1;
+    if run_len == 0 && prev_run_was_maximal {
+        // Like `encode_buf`, don't emit an empty final run after a maximal
+        // one; the space we reserved for its length byte gets the terminator.
+        output[code_pos] = ZERO;
+        code_pos + 1
+    } else {
+        output[code_pos] = encode_len(run_len);
+        output[out_pos] = ZERO;
+        out_pos + 1
     }
+}
 
-    // If we got here, it's because we ran all the way through `bytes` without
-    // finding the terminating ZERO.
-    Err(CobsError::Truncated)
+/// Encodes the concatenation of the slices in `parts` into COBS form, yielding
+/// individual encoded bytes through an iterator.
+///
+/// This is the iterator equivalent of [`encode_vectored_buf`], and produces the
+/// same output as `encode_iter` would on the concatenated message.
+pub fn encode_vectored_iter<'a>(
+    parts: &'a [&'a [u8]],
+) -> impl Iterator<Item = u8> + 'a {
+    let mut input = Gather::new(parts);
+    let mut state = Some(VectoredState::Begin);
+    core::iter::from_fn(move || loop {
+        match state? {
+            VectoredState::Begin => {
+                let (run_len, zero_follows) = input.measure_run();
+                state = Some(VectoredState::Run(run_len, zero_follows));
+                return Some(encode_len(run_len));
+            }
+            VectoredState::Run(0, zero_follows) => {
+                if zero_follows {
+                    // Drop the zero implied by the length byte.
+                    input.next();
+                    state = Some(VectoredState::Begin);
+                } else if input.is_empty() {
+                    state = Some(VectoredState::End);
+                } else {
+                    state = Some(VectoredState::Begin);
+                }
+            }
+            VectoredState::Run(n, zero_follows) => {
+                state = Some(VectoredState::Run(n - 1, zero_follows));
+                return input.next();
+            }
+            VectoredState::End => {
+                state = None;
+                return Some(ZERO);
+            }
+        }
+    })
 }
 
-/// Errors that can occur while decoding.
+/// State for incremental vectored encoding.
 #[derive(Copy, Clone, Debug)]
-pub enum CobsError {
-    /// The input ended without completing the last run or without the trailing
-    /// zero byte, suggesting that part of it is missing. (This can also occur
-    /// spuriously if you pick up in the middle of a stream without finding the
-    /// first zero.)
-    Truncated,
-    /// The input contained an unexpected zero byte. Not all decode methods
-    /// promise to detect this case.
-    Corrupt,
+enum VectoredState {
+    /// We are at a run boundary and need to emit a length byte.
+    Begin,
+    /// We are in a run with the given number of bytes left to emit. The `bool`
+    /// records whether the run is followed by a zero that is implied by the
+    /// length byte, and must be skipped.
+    Run(usize, bool),
+    /// We have used all the data bytes and just need to emit a terminating
+    /// zero.
+    End,
 }
 
-impl core::fmt::Display for CobsError {
-    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        match self {
-            Self::Truncated => f.write_str("input truncated"),
-            Self::Corrupt => f.write_str("input corrupt"),
+/// A cursor over the bytes of several slices, in order.
+#[derive(Copy, Clone, Debug)]
+struct Gather<'a> {
+    current: &'a [u8],
+    rest: &'a [&'a [u8]],
+}
+
+impl<'a> Gather<'a> {
+    fn new(parts: &'a [&'a [u8]]) -> Self {
+        Self { current: &[], rest: parts }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some((&b, current)) = self.current.split_first() {
+                self.current = current;
+                return Some(b);
+            }
+            let (&next, rest) = self.rest.split_first()?;
+            self.current = next;
+            self.rest = rest;
         }
     }
-}
 
-#[cfg(feature = "std")]
-impl std::error::Error for CobsError {}
+    fn is_empty(&self) -> bool {
+        self.current.is_empty() && self.rest.iter().all(|p| p.is_empty())
+    }
 
-/// Decodes a length-or-terminator byte. If the byte is `ZERO`, returns `None`.
-/// Otherwise returns the length of the run encoded by the byte.
-#[inline(always)]
-fn decode_len(code: u8) -> Option<usize> {
-    usize::from(code).checked_sub(1)
+    /// Scans (without consuming) the next run, which is between 0 and
+    /// `MAX_RUN` bytes, inclusive. Returns its length, and whether it is
+    /// terminated by a zero, as opposed to the max run length or the end of the
+    /// input.
+    fn measure_run(&self) -> (usize, bool) {
+        let mut scan = *self;
+        let mut len = 0;
+        while len < MAX_RUN {
+            match scan.next() {
+                Some(ZERO) => return (len, true),
+                Some(_) => len += 1,
+                None => break,
+            }
+        }
+        (len, false)
+    }
 }
 
-/// Decodes an encoded message, in-place. This is useful when you're short on
-/// memory. Since the decoded form of a COBS frame is always shorter than the
-/// encoded form, `bytes` is guaranteed to be long enough.
+/// Encodes `bytes` into COBS form without copying it, yielding a sequence of
+/// slices that, when concatenated, form the encoded message.
 ///
-/// The decoded message is deposited into `bytes` starting at index 0, and
-/// `decode_in_place` returns the number of decoded bytes.
+/// The slices alternate between single length bytes and runs of data borrowed
+/// directly from `bytes`, ending with the terminator. No yielded slice is
+/// empty.
 ///
-/// If you've got memory to spare, `decode_buf` is often somewhat faster --
-/// `decode_in_place` takes between 1x and 3x the time in benchmarks. You may
-/// also prefer to use `decode_buf` if you can't overwrite the incoming data,
-/// for whatever reason.
-pub fn decode_in_place(bytes: &mut [u8]) -> Result<usize, CobsError> {
-    let mut inpos = 0;
-    let mut outpos = 0;
-    let mut extra_zero = false;
-    while inpos < bytes.len() {
-        let head = bytes[inpos];
-        let n = if let Some(n) = decode_len(head) {
-            n
-        } else {
-            break;
-        };
-        if bytes.len() < inpos + 1 + n {
-            return Err(CobsError::Truncated);
-        }
-        bytes.copy_within(inpos + 1..inpos + 1 + n, outpos);
-        inpos += 1 + n;
-        outpos += n;
-        extra_zero = n != MAX_RUN;
-        if extra_zero {
-            bytes[outpos] = 0;
-            outpos += 1;
-        }
-    }
-    Ok(if extra_zero {
-        outpos - 1
-    } else {
-        outpos
+/// This is useful with output mechanisms that accept scatter lists, such as
+/// DMA engines with descriptor chains, or `std::io::Write::write_vectored`.
+/// For large messages with few zeroes, the number of segments is small, and
+/// the data never has to be copied at all.
+///
+/// ```
+/// let msg = [0x11, 0x22, 0x00, 0x33];
+/// let segments: Vec<&[u8]> = corncobs::encode_segments(&msg).collect();
+/// assert_eq!(segments, [
+///     &[0x03][..], &[0x11, 0x22], &[0x02], &[0x33], &[0x00],
+/// ]);
+/// ```
+pub fn encode_segments(bytes: &[u8]) -> impl Iterator<Item = &[u8]> + '_ {
+    let mut state = Some(SegmentState::Begin(bytes));
+    core::iter::from_fn(move || {
+        let (segment, s2) = state?.next();
+        state = s2;
+        Some(segment)
     })
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Default)]
-pub struct Decoder {
-    state: DState,
+/// State for segment-at-a-time encoding.
+#[derive(Copy, Clone, Debug)]
+enum SegmentState<'a> {
+    /// We are at a run boundary and need to emit a length byte.
+    Begin(&'a [u8]),
+    /// We have emitted the length byte for a non-empty run, and need to emit
+    /// the run itself, followed by whatever `take_run` said was left.
+    Run(&'a [u8], Option<&'a [u8]>),
+    /// We have used all the data bytes and just need to emit a terminating
+    /// zero.
+    End,
 }
 
-impl Decoder {
-    pub fn advance(&mut self, byte: u8) -> Result<DecodeStatus, CobsError> {
-        match self.state {
-            DState::Start | DState::Tween(true) => {
-                if let Some(count) = byte.checked_sub(1) {
-                    if let Some(count2) = count.checked_sub(1) {
-                        self.state = DState::Literal(count2, byte == 0xFF);
-                    } else {
-                        self.state = DState::Tween(false);
-                    }
-                    Ok(DecodeStatus::Pending)
-                } else {
+impl<'a> SegmentState<'a> {
+    fn next(self) -> (&'a [u8], Option<Self>) {
+        match self {
+            Self::Begin(bytes) => {
+                let (run, rest) = take_run(bytes);
+                let next = if run.is_empty() {
+                    Self::after_run(rest)
+                } else {
+                    Self::Run(run, rest)
+                };
+                (code_slice(encode_len(run.len())), Some(next))
+            }
+            Self::Run(run, rest) => (run, Some(Self::after_run(rest))),
+            Self::End => (code_slice(ZERO), None),
+        }
+    }
+
+    fn after_run(rest: Option<&'a [u8]>) -> Self {
+        if let Some(rest) = rest {
+            Self::Begin(rest)
+        } else {
+            Self::End
+        }
+    }
+}
+
+/// Returns a `'static` one-byte slice containing `code`, so that length bytes
+/// can be handed out without borrowing from anything.
+#[inline(always)]
+fn code_slice(code: u8) -> &'static [u8] {
+    static CODES: [u8; 256] = {
+        let mut codes = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            codes[i] = i as u8;
+            i += 1;
+        }
+        codes
+    };
+    let i = usize::from(code);
+    &CODES[i..i + 1]
+}
+
+/// Encodes the message `bytes` into the buffer `output`, like `encode_buf`,
+/// but returns an error instead of panicking if `output` is too small.
+///
+/// On success, returns the number of bytes used in `output`. If `output` can't
+/// hold the encoded form, returns `CobsError::OutputTooSmall` with the number
+/// of bytes that would have been needed; in this case, the contents of `output`
+/// are unspecified.
+///
+/// If you'd like to use whatever space is available and pick up where you left
+/// off, see [`EncodeCursor`].
+pub fn try_encode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+    if output.len() >= max_encoded_len(bytes.len()) {
+        // No way this can fail, use the fast path.
+        return Ok(encode_buf(bytes, output));
+    }
+    let mut cursor = EncodeCursor::new(bytes);
+    let n = cursor.fill(output);
+    if cursor.is_done() {
+        Ok(n)
+    } else {
+        Err(CobsError::OutputTooSmall {
+            needed: n + cursor.remaining_len(),
+        })
+    }
+}
+
+/// Resumable encoding state, for producing an encoded message a buffer at a
+/// time.
+///
+/// This lets you stream a message of any size through a small, fixed-size
+/// output buffer (such as a transmit FIFO or DMA buffer): each call to
+/// [`EncodeCursor::fill`] encodes as much as will fit, and remembers where it
+/// stopped.
+///
+/// ```
+/// let msg = [0xAA; 300];
+/// let mut cursor = corncobs::EncodeCursor::new(&msg);
+/// let mut tx = [0; 64];
+/// let mut total = 0;
+/// while !cursor.is_done() {
+///     let n = cursor.fill(&mut tx);
+///     // ... transmit tx[..n] ...
+///     total += n;
+/// }
+/// assert_eq!(total, corncobs::max_encoded_len(msg.len()));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct EncodeCursor<'a> {
+    /// Part of the current segment that hasn't been written yet.
+    partial: &'a [u8],
+    /// Where to find the next segment, or `None` if we're on the last one.
+    state: Option<SegmentState<'a>>,
+}
+
+impl<'a> EncodeCursor<'a> {
+    /// Creates a cursor positioned at the start of the encoded form of
+    /// `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            partial: &[],
+            state: Some(SegmentState::Begin(bytes)),
+        }
+    }
+
+    /// Writes as much of the encoded message as will fit into `output`, and
+    /// returns the number of bytes written. This will only return less than
+    /// `output.len()` if it reaches the end of the message.
+    pub fn fill(&mut self, output: &mut [u8]) -> usize {
+        let mut written = 0;
+        loop {
+            let space = output.len() - written;
+            let n = usize::min(self.partial.len(), space);
+            let (chunk, rest) = self.partial.split_at(n);
+            output[written..written + n].copy_from_slice(chunk);
+            written += n;
+            self.partial = rest;
+
+            if written == output.len() {
+                break;
+            }
+            if let Some(state) = self.state {
+                let (segment, next) = state.next();
+                self.partial = segment;
+                self.state = next;
+            } else {
+                break;
+            }
+        }
+        written
+    }
+
+    /// Checks whether the entire encoded message, including the terminator,
+    /// has been written out.
+    pub fn is_done(&self) -> bool {
+        self.partial.is_empty() && self.state.is_none()
+    }
+
+    /// Computes the number of encoded bytes that have yet to be written.
+    pub fn remaining_len(&self) -> usize {
+        let mut len = self.partial.len();
+        let mut state = self.state;
+        while let Some(s) = state {
+            let (segment, next) = s.next();
+            len += segment.len();
+            state = next;
+        }
+        len
+    }
+}
+
+/// Destination for encoded bytes produced by an [`Encoder`].
+///
+/// This is implemented for any `FnMut(&[u8])`, so in most cases you can just
+/// hand the `Encoder` a closure.
+pub trait Sink {
+    /// Accepts the next chunk of encoded output. Chunks arrive in order and
+    /// should be transmitted/stored back-to-back.
+    fn accept(&mut self, bytes: &[u8]);
+}
+
+impl<F: FnMut(&[u8])> Sink for F {
+    fn accept(&mut self, bytes: &[u8]) {
+        self(bytes)
+    }
+}
+
+/// Incremental COBS encoder.
+///
+/// Where `encode_buf` needs the entire message up front, an `Encoder` accepts
+/// the message in pieces, through [`Encoder::push`] and
+/// [`Encoder::push_byte`]. Encoded output is delivered to a [`Sink`] (often a
+/// closure) one run at a time, as soon as each run is complete. Call
+/// [`Encoder::end_frame`] or [`Encoder::finish`] to flush the final run and
+/// the terminator.
+///
+/// Because a COBS run can't be written until its length is known, the
+/// `Encoder` buffers up to one run (`MAX_RUN`, or 254, bytes) internally.
+///
+/// ```
+/// let mut out = vec![];
+/// let mut enc = corncobs::Encoder::new(|b: &[u8]| out.extend_from_slice(b));
+/// enc.push(&[0x11, 0x22]);
+/// enc.push_byte(0x00);
+/// enc.push(&[0x33]);
+/// enc.finish();
+///
+/// assert_eq!(out, [0x03, 0x11, 0x22, 0x02, 0x33, 0x00]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Encoder<S> {
+    sink: S,
+    /// The run being assembled. Index 0 is reserved for the length byte, so
+    /// that a finished run can be handed to the sink in one piece.
+    block: [u8; MAX_RUN + 1],
+    /// Number of data bytes in `block`, not counting the length byte.
+    len: usize,
+    /// Set when the most recently flushed run was maximal length and nothing
+    /// has arrived since. In this case, if the frame ends, we don't emit an
+    /// empty run before the terminator.
+    after_max: bool,
+}
+
+impl<S: Sink> Encoder<S> {
+    /// Creates an `Encoder` that will deliver output to `sink`.
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            block: [0; MAX_RUN + 1],
+            len: 0,
+            after_max: false,
+        }
+    }
+
+    /// Adds a single byte to the message.
+    pub fn push_byte(&mut self, byte: u8) {
+        if byte == ZERO {
+            self.flush_run();
+        } else {
+            self.len += 1;
+            self.block[self.len] = byte;
+            if self.len == MAX_RUN {
+                self.flush_run();
+                self.after_max = true;
+            }
+        }
+    }
+
+    /// Adds `bytes` to the message.
+    ///
+    /// This is significantly faster than calling `push_byte` in a loop, since
+    /// it can move non-zero bytes in blocks.
+    pub fn push(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // Copy as much of the next run as we have room for.
+            let max_len = usize::min(bytes.len(), MAX_RUN - self.len);
+            let run_len = bytes[..max_len]
+                .iter()
+                .position(|&b| b == ZERO)
+                .unwrap_or(max_len);
+            let (run, rest) = bytes.split_at(run_len);
+            self.block[1 + self.len..1 + self.len + run_len]
+                .copy_from_slice(run);
+            self.len += run_len;
+            bytes = rest;
+
+            if self.len == MAX_RUN {
+                self.flush_run();
+                self.after_max = true;
+            } else if let Some((_zero, rest)) = bytes.split_first() {
+                // We stopped short of both the end of the input and the max
+                // run length, so we must be looking at a zero.
+                debug_assert_eq!(_zero, &ZERO);
+                self.flush_run();
+                bytes = rest;
+            }
+        }
+    }
+
+    /// Ends the current message, flushing any buffered data and the
+    /// terminating zero to the sink. The `Encoder` can then be used to encode
+    /// another message.
+    pub fn end_frame(&mut self) {
+        // Like `encode_buf`, we omit the final empty run if it follows a
+        // maximal run.
+        if self.len != 0 || !self.after_max {
+            self.flush_run();
+        }
+        self.sink.accept(&[ZERO]);
+        self.after_max = false;
+    }
+
+    /// Ends the current message (as with [`Encoder::end_frame`]) and drops the
+    /// `Encoder`.
+    pub fn finish(mut self) {
+        self.end_frame();
+    }
+
+    /// Gets a reference to the sink.
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Gets a mutable reference to the sink.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the sink. Any partial message that has not been ended with
+    /// [`Encoder::end_frame`] is discarded.
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// Writes out the run being assembled, and starts a new one.
+    fn flush_run(&mut self) {
+        self.block[0] = encode_len(self.len);
+        self.sink.accept(&self.block[..self.len + 1]);
+        self.len = 0;
+        self.after_max = false;
+    }
+}
+
+/// Decodes `bytes` into a vector.
+///
+/// This is a convenience for cases where you have `std` available. Its behavior
+/// is otherwise identical to `decode_buf`.
+#[cfg(feature = "std")]
+pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
+    let offset = output.len();
+    output.resize(offset + bytes.len(), 0);
+    let actual_len = decode_buf(bytes, &mut output[offset..])?;
+    output.truncate(offset + actual_len);
+    Ok(())
+}
+
+/// Decodes input from `bytes` into `output` starting at index 0. Returns the
+/// number of bytes used in `output`.
+///
+/// The decoded form is always shorter than the encoded form, so `output` is
+/// guaranteed to be large enough if it's at least `bytes.len()` bytes long. If
+/// `output` turns out to be too small, returns `CobsError::OutputTooSmall` with
+/// the number of bytes the decoded message needs; in this case, the contents of
+/// `output` are unspecified. Since the size of the decoded message is
+/// determined by whoever sent it, you'll want to handle this error if you're
+/// using a smaller buffer.
+pub fn decode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+    decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+}
+
+/// Decodes input from `bytes` into `output` starting at index 0, like
+/// `decode_buf`, but also reports how many bytes of `bytes` made up the
+/// message, including the terminator.
+///
+/// This is useful when `bytes` contains several messages back to back: the
+/// next message starts at `bytes[consumed..]`, and you don't have to scan for
+/// the terminator yourself.
+///
+/// ```
+/// let input = [0x02, 0x11, 0x00, 0x03, 0x22, 0x33, 0x00];
+/// let mut output = [0; 7];
+///
+/// let first = corncobs::decode_buf_with_consumed(&input, &mut output).unwrap();
+/// assert_eq!(first.decoded_len, 1);
+/// assert_eq!(first.consumed, 3);
+///
+/// let rest = &input[first.consumed..];
+/// let second = corncobs::decode_buf_with_consumed(rest, &mut output).unwrap();
+/// assert_eq!(&output[..second.decoded_len], [0x22, 0x33]);
+/// assert_eq!(second.consumed, rest.len());
+/// ```
+pub fn decode_buf_with_consumed(bytes: &[u8], output: &mut [u8]) -> Result<Decoded, CobsError> {
+    decode_buf_core::<u8, false, false, ZERO>(bytes, output).map_err(|e| e.kind)
+}
+
+/// Implementation of buffer-to-buffer decoding, for any symbol type. If
+/// `STRICT` is set, we check each run for zeroes as we copy it. If `CANONICAL`
+/// is set, we also reject encodings that `encode_buf` wouldn't produce (see
+/// `is_canonical`).
+fn decode_buf_core<S: Symbol, const STRICT: bool, const CANONICAL: bool, const DELIM: u8>(
+    mut bytes: &[S],
+    mut output: &mut [S],
+) -> Result<Decoded, DecodeError<S>> {
+    let delim = S::from(DELIM);
+    let orig_len = output.len();
+    let orig_in_len = bytes.len();
+
+    let mut trailing_zero = false;
+    // Whether the last run could legitimately end the message, and where it
+    // started, for `CANONICAL`.
+    let mut canonical_end = false;
+    let mut last_run = (0, None);
+    // Length byte of a run that was cut off, if any, for error reporting.
+    let mut cut_off = None;
+    // This while-loop is equivalent to `for b in bytes` except that it lets us
+    // _also_ consume bytes inside the body, which we totally do.
+    while let Some((&head, rest)) = bytes.split_first() {
+        let offset = orig_in_len - bytes.len();
+        // Detect message terminator.
+        let n = if let Some(n) = (head ^ delim).decode_len() {
+            n
+        } else {
+            if CANONICAL && !canonical_end {
+                let (offset, code) = last_run;
+                return Err(DecodeError {
+                    kind: CobsError::NonCanonical,
+                    offset,
+                    code,
+                    output_len: orig_len - output.len(),
+                });
+            }
+            return Ok(Decoded {
+                decoded_len: orig_len - output.len(),
+                consumed: orig_in_len - rest.len(),
+            });
+        };
+        // Refuse to proceed if the output can't hold this run, plus the zero
+        // we may need to insert before it. (This check prevents a panic in
+        // decoding oversized messages.)
+        if output.len() < usize::from(trailing_zero) + n {
+            let start = ScanStart {
+                offset,
+                decoded_len: orig_len - output.len(),
+                trailing_zero,
+            };
+            let checks = DecodeOptions::new().strict(STRICT).canonical(CANONICAL);
+            return Err(output_too_small::<S, DELIM>(bytes, start, checks));
+        }
+        bytes = rest;
+        // If we're not at the end of the message, and our last run was less
+        // than MAX_RUN bytes, we need to insert a zero.
+        if trailing_zero {
+            let (z, new_output) = output.split_at_mut(1);
+            z[0] = delim;
+            output = new_output;
+        }
+
+        // Skip a bunch of work if our run length is zero. This cuts the
+        // worst-case time in benchmarks (on Intel) by about 50% while only
+        // slightly impacting the less pathological cases.
+        if n != 0 {
+            // Refuse to proceed if the run claims to contain more bytes than
+            // the slice does. (This check prevents a panic in decoding
+            // truncated data.)
+            if bytes.len() < n {
+                if STRICT {
+                    if let Some(i) = bytes.iter().position(|&b| b == delim) {
+                        return Err(DecodeError {
+                            kind: CobsError::Corrupt,
+                            offset: offset + 1 + i,
+                            code: Some(head),
+                            output_len: orig_len - output.len() + i,
+                        });
+                    }
+                }
+                cut_off = Some(head);
+                break;
+            }
+
+            // Split the remaining data into the block belonging to this run and
+            // allll the rest.
+            let (block, rest) = bytes.split_at(n);
+            bytes = rest;
+            if STRICT {
+                if let Some(i) = block.iter().position(|&b| b == delim) {
+                    return Err(DecodeError {
+                        kind: CobsError::Corrupt,
+                        offset: offset + 1 + i,
+                        code: Some(head),
+                        output_len: orig_len - output.len() + i,
+                    });
+                }
+            }
+
+            // Blit that block!
+            let (block_out, new_output) = output.split_at_mut(block.len());
+            block_out.copy_from_slice(block);
+            output = new_output;
+        }
+
+        // Record whether this run was shorter than the max. Runs shorter than
+        // the max in the middle of a message are always ended by zero, which we
+        // need to insert in the output. However, a shorter-than-max run at the
+        // very _end_ is not terminated by zero, and we handle it above.
+        canonical_end = ends_canonically(n, trailing_zero, offset == 0);
+        last_run = (offset, Some(head));
+        trailing_zero = n != S::MAX_RUN;
+    }
+
+    // If we got here, it's because we ran all the way through `bytes` without
+    // finding the terminating ZERO.
+    Err(DecodeError {
+        kind: CobsError::Truncated,
+        offset: orig_in_len,
+        code: cut_off,
+        output_len: orig_len - output.len() + bytes.len(),
+    })
+}
+
+/// Decodes a message from a circular buffer, where the message may wrap around
+/// from the end of the buffer to the start.
+///
+/// `halves` gives the contents of the buffer in order, as two slices: the part
+/// from the read position to the end of the buffer, and the part that wrapped
+/// around to the start. (This is the same convention used by
+/// `VecDeque::as_slices`.) Either may be empty. The message is decoded into
+/// `output` starting at index 0, as with `decode_buf`, and the result reports
+/// how many bytes of each half the message used, including the terminator, so
+/// that you can advance your read position.
+///
+/// ```
+/// // A ring buffer where the message `11 22 00 33` wrapped around.
+/// let ring = [0x02, 0x33, 0x00, 0xDE, 0xDE, 0x03, 0x11, 0x22];
+/// let halves = (&ring[5..], &ring[..3]);
+/// let mut output = [0; 8];
+///
+/// let d = corncobs::decode_ring_buf(halves, &mut output).unwrap();
+/// assert_eq!(&output[..d.decoded_len], [0x11, 0x22, 0x00, 0x33]);
+/// assert_eq!(d.consumed, (3, 3));
+/// ```
+pub fn decode_ring_buf(
+    halves: (&[u8], &[u8]),
+    output: &mut [u8],
+) -> Result<RingDecoded, CobsError> {
+    decode_ring_buf_core::<false, false>(halves, output).map_err(|e| e.kind)
+}
+
+/// Implementation of ring buffer decoding. `STRICT` and `CANONICAL` are as for
+/// `decode_buf_core`, and so are the details of any error, with offsets
+/// counted from the start of the first half.
+fn decode_ring_buf_core<const STRICT: bool, const CANONICAL: bool>(
+    halves: (&[u8], &[u8]),
+    output: &mut [u8],
+) -> Result<RingDecoded, DecodeError> {
+    let (first, second) = halves;
+
+    // Most messages won't wrap. Try to decode from the first half alone, so
+    // that they go as fast as they would with `decode_buf`.
+    match decode_buf_core::<u8, STRICT, CANONICAL, ZERO>(first, output) {
+        Ok(d) => {
+            return Ok(RingDecoded {
+                decoded_len: d.decoded_len,
+                consumed: (d.consumed, 0),
+            })
+        }
+        Err(e) if e.kind == CobsError::Truncated && !second.is_empty() => (),
+        Err(e) => return Err(e),
+    }
+
+    // Otherwise, we do this the slow way, where any access to the input might
+    // be to either half.
+    let total = first.len() + second.len();
+    let byte_at = |i: usize| {
+        if i < first.len() {
+            first[i]
+        } else {
+            second[i - first.len()]
+        }
+    };
+    let copy_from = |i: usize, out: &mut [u8]| {
+        if let Some(j) = i.checked_sub(first.len()) {
+            out.copy_from_slice(&second[j..j + out.len()]);
+        } else {
+            let k = usize::min(first.len() - i, out.len());
+            let (out_first, out_second) = out.split_at_mut(k);
+            out_first.copy_from_slice(&first[i..i + k]);
+            out_second.copy_from_slice(&second[..out_second.len()]);
+        }
+    };
+    let find_zero = |range: core::ops::Range<usize>| {
+        (range.start..range.end.min(total)).find(|&i| byte_at(i) == ZERO)
+    };
+
+    let mut inpos = 0;
+    let mut outpos = 0;
+    let mut trailing_zero = false;
+    let mut canonical_end = false;
+    let mut last_run = (0, None);
+    // Once we run out of output space, we keep walking the runs without
+    // writing anything, so we can say how much space we needed. This records
+    // the run that didn't fit, and the output length before it.
+    let mut overflow = None;
+    while inpos < total {
+        let head = byte_at(inpos);
+        let n = if let Some(n) = decode_len(head) {
+            n
+        } else {
+            if CANONICAL && !canonical_end {
+                let (offset, code) = last_run;
+                return Err(DecodeError {
+                    kind: CobsError::NonCanonical,
+                    offset,
+                    code,
+                    output_len: outpos,
+                });
+            }
+            if let Some((offset, code, output_len)) = overflow {
+                return Err(DecodeError {
+                    kind: CobsError::OutputTooSmall { needed: outpos },
+                    offset,
+                    code: Some(code),
+                    output_len,
+                });
+            }
+            let consumed = inpos + 1;
+            return Ok(RingDecoded {
+                decoded_len: outpos,
+                consumed: if consumed <= first.len() {
+                    (consumed, 0)
+                } else {
+                    (first.len(), consumed - first.len())
+                },
+            });
+        };
+        // Output produced before the byte at `i`, inside this run.
+        let output_len = |i: usize| outpos + usize::from(trailing_zero) + i - (inpos + 1);
+        if STRICT {
+            if let Some(i) = find_zero(inpos + 1..inpos + 1 + n) {
+                return Err(DecodeError {
+                    kind: CobsError::Corrupt,
+                    offset: i,
+                    code: Some(head),
+                    output_len: output_len(i),
+                });
+            }
+        }
+        if total < inpos + 1 + n {
+            return Err(DecodeError {
+                kind: CobsError::Truncated,
+                offset: total,
+                code: Some(head),
+                output_len: output_len(total),
+            });
+        }
+        let run_out = usize::from(trailing_zero) + n;
+        if overflow.is_none() && output.len() < outpos + run_out {
+            overflow = Some((inpos, head, outpos));
+        }
+        if overflow.is_none() {
+            if trailing_zero {
+                output[outpos] = ZERO;
+            }
+            copy_from(inpos + 1, &mut output[outpos + run_out - n..outpos + run_out]);
+        }
+        canonical_end = ends_canonically(n, trailing_zero, inpos == 0);
+        last_run = (inpos, Some(head));
+        inpos += 1 + n;
+        outpos += run_out;
+        trailing_zero = n != MAX_RUN;
+    }
+
+    Err(DecodeError {
+        kind: CobsError::Truncated,
+        offset: total,
+        code: None,
+        output_len: outpos,
+    })
+}
+
+/// Sizes reported by [`decode_ring_buf`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RingDecoded {
+    /// Length of the decoded message.
+    pub decoded_len: usize,
+    /// Number of bytes of input that made up the encoded message, including
+    /// the terminator, from the first and second halves of the input,
+    /// respectively.
+    pub consumed: (usize, usize),
+}
+
+/// Sizes reported by the decode routines that deal in whole frames, such as
+/// [`decode_buf_with_consumed`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    /// Length of the decoded message.
+    pub decoded_len: usize,
+    /// Number of bytes of input that made up the encoded message, including
+    /// the terminator.
+    pub consumed: usize,
+}
+
+/// Produces the error for `decode_buf` running out of output space, having
+/// reached the run that starts at `bytes[0]`. The arguments are as for
+/// `scan_runs`.
+///
+/// If the rest of the message turns out to be damaged, we report that instead,
+/// since there's no size of buffer that would have helped.
+#[cold]
+fn output_too_small<S: Symbol, const DELIM: u8>(
+    bytes: &[S],
+    start: ScanStart,
+    checks: DecodeOptions,
+) -> DecodeError<S> {
+    match scan_runs::<S, DELIM>(bytes, start, checks) {
+        Ok(info) => DecodeError {
+            kind: CobsError::OutputTooSmall {
+                needed: info.decoded_len,
+            },
+            offset: start.offset,
+            code: bytes.first().copied(),
+            output_len: start.decoded_len,
+        },
+        Err(e) => e,
+    }
+}
+
+/// Describes where `scan_runs` is starting from, which may be partway through a
+/// message.
+#[derive(Copy, Clone, Debug, Default)]
+struct ScanStart {
+    /// Offset of the first byte we're given, from the start of the message.
+    offset: usize,
+    /// Number of bytes decoded so far.
+    decoded_len: usize,
+    /// Same meaning as in `decode_buf`.
+    trailing_zero: bool,
+}
+
+/// Walks the chain of runs starting at `bytes[0]` without copying anything,
+/// and describes what it found. Sizes and offsets (in both the result and any
+/// error) are from the start of the message, as described by `start`, except
+/// for the number of runs, which only counts the ones in `bytes`. `checks` says
+/// which checks to apply, as in `DecodeOptions`.
+fn scan_runs<S: Symbol, const DELIM: u8>(
+    mut bytes: &[S],
+    start: ScanStart,
+    checks: DecodeOptions,
+) -> Result<FrameInfo, DecodeError<S>> {
+    let delim = S::from(DELIM);
+    let strict = checks.strict || checks.canonical;
+    let ScanStart {
+        mut offset,
+        decoded_len: mut len,
+        mut trailing_zero,
+    } = start;
+    let mut canonical_end = false;
+    let mut last_run = (offset, None);
+    let mut runs = 0;
+    while let Some((&head, rest)) = bytes.split_first() {
+        let n = if let Some(n) = (head ^ delim).decode_len() {
+            n
+        } else {
+            if checks.canonical && !canonical_end {
+                let (offset, code) = last_run;
+                return Err(DecodeError {
+                    kind: CobsError::NonCanonical,
+                    offset,
+                    code,
+                    output_len: len,
+                });
+            }
+            return Ok(FrameInfo {
+                decoded_len: len,
+                consumed: offset + 1,
+                runs,
+            });
+        };
+        if strict {
+            if let Some(i) = rest[..n.min(rest.len())].iter().position(|&b| b == delim) {
+                return Err(DecodeError {
+                    kind: CobsError::Corrupt,
+                    offset: offset + 1 + i,
+                    code: Some(head),
+                    output_len: len + usize::from(trailing_zero) + i,
+                });
+            }
+        }
+        if rest.len() < n {
+            return Err(DecodeError {
+                kind: CobsError::Truncated,
+                offset: offset + 1 + rest.len(),
+                code: Some(head),
+                output_len: len + usize::from(trailing_zero) + rest.len(),
+            });
+        }
+        bytes = &rest[n..];
+        len += usize::from(trailing_zero) + n;
+        runs += 1;
+        canonical_end = ends_canonically(n, trailing_zero, offset == 0);
+        last_run = (offset, Some(head));
+        trailing_zero = n != S::MAX_RUN;
+        offset += 1 + n;
+    }
+    Err(DecodeError {
+        kind: CobsError::Truncated,
+        offset,
+        code: None,
+        output_len: len,
+    })
+}
+
+/// Checks whether a run of length `n` could be the last run in a message
+/// produced by `encode_buf`. `trailing_zero` and `at_start` describe what came
+/// before the run, as in `scan_runs`.
+///
+/// The only run that can't is an empty one directly after a run of `MAX_RUN`
+/// bytes, since the decoder would give the same result without it. (Except at
+/// the start, where the empty run is how we encode an empty message.)
+#[inline(always)]
+fn ends_canonically(n: usize, trailing_zero: bool, at_start: bool) -> bool {
+    n != 0 || trailing_zero || at_start
+}
+
+/// Errors that can occur while encoding or decoding.
+///
+/// New kinds of error may be added without a major version bump, so a `match`
+/// on this needs a wildcard arm.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CobsError {
+    /// The input ended without completing the last run or without the trailing
+    /// zero byte, suggesting that part of it is missing. (This can also occur
+    /// spuriously if you pick up in the middle of a stream without finding the
+    /// first zero.)
+    Truncated,
+    /// The input contained an unexpected zero byte. Not all decode methods
+    /// promise to detect this case.
+    Corrupt,
+    /// The output buffer was too small to hold the result. `needed` gives the
+    /// size that would have been enough.
+    OutputTooSmall {
+        /// Required size of the output buffer, in bytes.
+        needed: usize,
+    },
+    /// The frame exceeded the maximum length set with
+    /// [`Decoder::with_max_len`].
+    TooLong,
+    /// The input decoded correctly, but wasn't encoded the way `encode_buf`
+    /// would have done it, and we were asked to check for that (see
+    /// [`DecodeOptions::canonical`]).
+    NonCanonical,
+}
+
+impl core::fmt::Display for CobsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("input truncated"),
+            Self::Corrupt => f.write_str("input corrupt"),
+            Self::OutputTooSmall { needed } => {
+                write!(f, "output buffer too small (need {} bytes)", needed)
+            }
+            Self::TooLong => f.write_str("frame too long"),
+            Self::NonCanonical => f.write_str("input not canonically encoded"),
+        }
+    }
+}
+
+impl core::error::Error for CobsError {}
+
+/// An error from decoding, along with where it happened, for diagnosing
+/// damaged messages.
+///
+/// Each of the decoders has a way of reporting these details:
+///
+/// - For buffers, [`DecodeOptions::decode_buf_detailed`],
+///   [`DecodeOptions::decode_in_place_detailed`],
+///   [`DecodeOptions::decode_ring_buf_detailed`] and
+///   [`DecodeOptions::validate_detailed`]. The free functions, such as
+///   [`decode_buf`], behave like `DecodeOptions::new()`, and return just the
+///   [`CobsError`].
+/// - For the [`Decoder`], [`Decoder::advance_detailed`], and the
+///   [`FeedError`] returned by `feed`, which converts into a `DecodeError`.
+///   These count offsets from the start of the frame.
+/// - The frame iterators, such as [`frames`], and the [`FrameReceiver`], which
+///   return `DecodeError` directly.
+///
+/// The type parameter is the symbol type, which is always the default, `u8`,
+/// in this crate's API.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError<S = u8> {
+    /// What went wrong.
+    pub kind: CobsError,
+    /// Offset in the input where the problem was found. Depending on `kind`,
+    /// this is:
+    ///
+    /// - `Corrupt`: the unexpected zero byte.
+    /// - `Truncated`: the end of the input.
+    /// - `OutputTooSmall`: the length byte of the run that didn't fit.
+    /// - `NonCanonical`: the length byte of the run that shouldn't be there
+    ///   (or, for an empty message with no runs, the terminator).
+    pub offset: usize,
+    /// Length byte of the run in which the problem was found, if it was in
+    /// one.
+    pub code: Option<S>,
+    /// Number of bytes of the message that were decoded before the problem
+    /// was found.
+    pub output_len: usize,
+}
+
+impl<S: core::fmt::LowerHex> core::fmt::Display for DecodeError<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        if let Some(code) = &self.code {
+            write!(f, ", in run with length byte {:#04x}", code)?;
+        }
+        write!(f, ", after {} bytes of output", self.output_len)
+    }
+}
+
+impl<S: core::fmt::Debug + core::fmt::LowerHex> core::error::Error for DecodeError<S> {}
+
+impl<S> From<DecodeError<S>> for CobsError {
+    fn from(e: DecodeError<S>) -> Self {
+        e.kind
+    }
+}
+
+/// Decodes a length-or-terminator byte. If the byte is `ZERO`, returns `None`.
+/// Otherwise returns the length of the run encoded by the byte.
+#[inline(always)]
+fn decode_len(code: u8) -> Option<usize> {
+    usize::from(code).checked_sub(1)
+}
+
+/// Decodes an encoded message, in-place. This is useful when you're short on
+/// memory. Since the decoded form of a COBS frame is always shorter than the
+/// encoded form, `bytes` is guaranteed to be long enough.
+///
+/// The decoded message is deposited into `bytes` starting at index 0, and
+/// `decode_in_place` returns the number of decoded bytes.
+///
+/// If you've got memory to spare, `decode_buf` is often somewhat faster --
+/// `decode_in_place` takes between 1x and 3x the time in benchmarks. You may
+/// also prefer to use `decode_buf` if you can't overwrite the incoming data,
+/// for whatever reason.
+pub fn decode_in_place(bytes: &mut [u8]) -> Result<usize, CobsError> {
+    decode_in_place_inner::<u8, false, false, ZERO>(bytes)
+        .map(|(decoded_len, _)| decoded_len)
+        .map_err(|e| e.kind)
+}
+
+/// Decodes an encoded message in-place, like `decode_in_place`, but also
+/// reports how many bytes of `bytes` made up the message, including the
+/// terminator. The next message, if any, starts at `bytes[consumed..]`.
+///
+/// Unlike `decode_in_place`, this requires the terminator to be present, and
+/// returns `CobsError::Truncated` if it isn't.
+pub fn decode_in_place_with_consumed(bytes: &mut [u8]) -> Result<Decoded, CobsError> {
+    DecodeOptions::new().decode_in_place_with_consumed(bytes)
+}
+
+/// Implementation of in-place decoding. Returns the decoded length and, if we
+/// found a terminator, the number of bytes consumed including it. `S`,
+/// `STRICT` and `CANONICAL` are as for `decode_buf_core`, and so are the
+/// details of any error.
+fn decode_in_place_inner<S: Symbol, const STRICT: bool, const CANONICAL: bool, const DELIM: u8>(
+    bytes: &mut [S],
+) -> Result<(usize, Option<usize>), DecodeError<S>> {
+    let delim = S::from(DELIM);
+    let mut inpos = 0;
+    let mut outpos = 0;
+    let mut extra_zero = false;
+    let mut canonical_end = false;
+    let mut last_run = (0, None);
+    let mut consumed = None;
+    while inpos < bytes.len() {
+        let head = bytes[inpos];
+        let n = if let Some(n) = (head ^ delim).decode_len() {
+            n
+        } else {
+            if CANONICAL && !canonical_end {
+                let (offset, code) = last_run;
+                return Err(DecodeError {
+                    kind: CobsError::NonCanonical,
+                    offset,
+                    code,
+                    output_len: outpos - usize::from(extra_zero),
+                });
+            }
+            consumed = Some(inpos + 1);
+            break;
+        };
+        // Unlike `decode_buf_core`, we've already written out the zero that
+        // goes before this run, so it's counted in `outpos`.
+        let end = usize::min(inpos + 1 + n, bytes.len());
+        if STRICT {
+            if let Some(i) = bytes[inpos + 1..end].iter().position(|&b| b == delim) {
+                return Err(DecodeError {
+                    kind: CobsError::Corrupt,
+                    offset: inpos + 1 + i,
+                    code: Some(head),
+                    output_len: outpos + i,
+                });
+            }
+        }
+        if bytes.len() < inpos + 1 + n {
+            return Err(DecodeError {
+                kind: CobsError::Truncated,
+                offset: bytes.len(),
+                code: Some(head),
+                output_len: outpos + end - (inpos + 1),
+            });
+        }
+        bytes.copy_within(inpos + 1..inpos + 1 + n, outpos);
+        canonical_end = ends_canonically(n, extra_zero, inpos == 0);
+        last_run = (inpos, Some(head));
+        inpos += 1 + n;
+        outpos += n;
+        extra_zero = n != S::MAX_RUN;
+        if extra_zero {
+            bytes[outpos] = delim;
+            outpos += 1;
+        }
+    }
+    let decoded_len = if extra_zero {
+        outpos - 1
+    } else {
+        outpos
+    };
+    if STRICT && consumed.is_none() {
+        // Strict decoding needs the terminator, just as `decode_buf` does.
+        return Err(DecodeError {
+            kind: CobsError::Truncated,
+            offset: bytes.len(),
+            code: None,
+            output_len: decoded_len,
+        });
+    }
+    Ok((decoded_len, consumed))
+}
+
+/// Checks whether `bytes` is exactly what `encode_buf` would produce for some
+/// message: a single encoded message, including its terminator, and nothing
+/// else.
+///
+/// COBS decoders (including ours) accept some encodings that encoders never
+/// produce, so the same message can arrive encoded in more than one way. In
+/// particular:
+///
+/// - A message can end with an empty run directly after a run of 254 bytes,
+///   which decodes to nothing (`FF <254 bytes> 01 00`, where `encode_buf`
+///   would produce `FF <254 bytes> 00`).
+/// - The empty message can be sent as a bare terminator (`00`), where
+///   `encode_buf` produces `01 00`.
+///
+/// Encodings containing zeroes inside a run aren't canonical either, and
+/// neither is anything truncated or with trailing data.
+///
+/// To normalize a message that isn't canonical, decode it and encode it again.
+///
+/// ```
+/// assert!(corncobs::is_canonical(&[0x01, 0x00]));
+/// assert!(corncobs::is_canonical(&[0x02, 0x11, 0x00]));
+/// assert!(!corncobs::is_canonical(&[0x00]));
+/// assert!(!corncobs::is_canonical(&[0x02, 0x11, 0x00, 0x00]));
+/// ```
+pub fn is_canonical(bytes: &[u8]) -> bool {
+    match DecodeOptions::new().canonical(true).validate(bytes) {
+        Ok(info) => info.consumed == bytes.len(),
+        Err(_) => false,
+    }
+}
+
+/// Checks the encoded message at the start of `bytes`, without decoding it.
+///
+/// This walks the chain of runs that make up the message, the way `decode_buf`
+/// would, but doesn't copy anything, so it needs no output buffer. If the
+/// message is intact (by `decode_buf`'s standards), returns a [`FrameInfo`]
+/// describing it; otherwise, returns the error that `decode_buf` would have.
+///
+/// You can use this to find out how big a buffer to allocate for a message
+/// before decoding it, or to check a message you're going to pass along
+/// without decoding. For stricter checks, see [`DecodeOptions::validate`].
+///
+/// ```
+/// let input = [0x03, 0x11, 0x22, 0x02, 0x33, 0x00, 0xAA];
+/// let info = corncobs::validate(&input).unwrap();
+/// assert_eq!(info.decoded_len, 4);
+/// assert_eq!(info.consumed, 6);
+/// assert_eq!(info.runs, 2);
+/// ```
+pub fn validate(bytes: &[u8]) -> Result<FrameInfo, CobsError> {
+    DecodeOptions::new().validate(bytes)
+}
+
+/// Description of an encoded message, produced by [`validate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// Length the message will have once decoded.
+    pub decoded_len: usize,
+    /// Number of bytes of input that make up the encoded message, including
+    /// the terminator.
+    pub consumed: usize,
+    /// Number of runs the message is made of, each of which starts with a
+    /// length byte. The terminator isn't counted.
+    pub runs: usize,
+}
+
+/// Settings for decoding, for when the behavior of the plain decode functions
+/// isn't what you want.
+///
+/// Build one with `DecodeOptions::new()` and the setter methods, and then use
+/// its decode methods in place of the free functions. (It's a `const fn`, so
+/// you can keep your settings in a `const`.) The frame iterators take options
+/// through [`DecodeOptions::frames`] and [`DecodeOptions::frames_in_place`],
+/// and, with the `std` feature, so does `CobsReader::with_options`.
+///
+/// ```
+/// use corncobs::{CobsError, DecodeOptions};
+///
+/// const STRICT: DecodeOptions = DecodeOptions::new().strict(true);
+///
+/// // A message that was cut short, and followed by another.
+/// let input = [0x04, 0x11, 0x22, 0x00, 0x02, 0x33, 0x00];
+/// let mut output = [0; 8];
+/// // Normally, these are merged into garbage:
+/// assert!(corncobs::decode_buf(&input, &mut output).is_ok());
+/// // but in strict mode, we notice.
+/// assert_eq!(STRICT.decode_buf(&input, &mut output), Err(CobsError::Corrupt));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    strict: bool,
+    canonical: bool,
+}
+
+/// Evaluates `$body` with consts named `$strict` and `$canonical` set to match
+/// `$options`, for picking the instance of a decoder that makes the checks it
+/// asks for. (Canonical checking implies strict, so there are three cases.)
+macro_rules! with_checks {
+    ($options:expr, |$strict:ident, $canonical:ident| $body:expr) => {
+        match ($options.strict, $options.canonical) {
+            (_, true) => {
+                const $strict: bool = true;
+                const $canonical: bool = true;
+                $body
+            }
+            (true, false) => {
+                const $strict: bool = true;
+                const $canonical: bool = false;
+                $body
+            }
+            (false, false) => {
+                const $strict: bool = false;
+                const $canonical: bool = false;
+                $body
+            }
+        }
+    };
+}
+
+impl DecodeOptions {
+    /// Creates the default options, which decode the same way as the free
+    /// functions, like `decode_buf`.
+    pub const fn new() -> Self {
+        Self {
+            strict: false,
+            canonical: false,
+        }
+    }
+
+    /// Enables or disables strict validation.
+    ///
+    /// In strict mode, any zero byte in the input before the end of the
+    /// message (that is, inside a run) is rejected with `CobsError::Corrupt`,
+    /// rather than being copied into the output. This is the same check that
+    /// `Decoder` always does, and in strict mode, all the decode methods agree
+    /// with `Decoder` (and with one another) on which inputs are corrupt.
+    ///
+    /// This costs some speed, since it means looking at every byte. See the
+    /// "Design decisions" section of the crate docs for why it's not the
+    /// default.
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Enables or disables the canonical-encoding check.
+    ///
+    /// With this enabled, messages that decode correctly but aren't encoded
+    /// the way `encode_buf` would have encoded them are rejected with
+    /// `CobsError::NonCanonical`. See [`is_canonical`] for details. This also
+    /// implies strict validation.
+    ///
+    /// This is useful if you compare or sign the encoded form of a message,
+    /// since it guarantees that each message has only one encoding.
+    pub const fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Decodes input from `bytes` into `output` starting at index 0, like
+    /// [`decode_buf`], using these options. Returns the number of bytes used
+    /// in `output`.
+    pub fn decode_buf(&self, bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+        self.decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+    }
+
+    /// Decodes input from `bytes` into `output` starting at index 0, like
+    /// [`decode_buf_with_consumed`], using these options.
+    pub fn decode_buf_with_consumed(
+        &self,
+        bytes: &[u8],
+        output: &mut [u8],
+    ) -> Result<Decoded, CobsError> {
+        self.decode_buf_detailed(bytes, output).map_err(|e| e.kind)
+    }
+
+    /// Decodes input from `bytes` into `output` starting at index 0, like
+    /// [`DecodeOptions::decode_buf_with_consumed`], but on failure, reports
+    /// where in the input the problem was found.
+    ///
+    /// This is a little slower on failure (and only on failure), so you may
+    /// want to use it only when you care about the details, such as when
+    /// analyzing a capture.
+    ///
+    /// ```
+    /// let strict = corncobs::DecodeOptions::new().strict(true);
+    /// let input = [0x02, 0x11, 0x04, 0x22, 0x00, 0x33, 0x00];
+    /// let mut output = [0; 8];
+    ///
+    /// let e = strict.decode_buf_detailed(&input, &mut output).unwrap_err();
+    /// assert_eq!(e.kind, corncobs::CobsError::Corrupt);
+    /// assert_eq!(e.offset, 4);
+    /// assert_eq!(e.code, Some(0x04));
+    /// assert_eq!(e.output_len, 3);
+    /// assert_eq!(
+    ///     e.to_string(),
+    ///     "input corrupt at offset 4, in run with length byte 0x04, \
+    ///      after 3 bytes of output",
+    /// );
+    /// ```
+    pub fn decode_buf_detailed(
+        &self,
+        bytes: &[u8],
+        output: &mut [u8],
+    ) -> Result<Decoded, DecodeError> {
+        with_checks!(self, |STRICT, CANONICAL| {
+            decode_buf_core::<u8, STRICT, CANONICAL, ZERO>(bytes, output)
+        })
+    }
+
+    /// Decodes a message in-place, like [`decode_in_place`], using these
+    /// options. Returns the number of decoded bytes.
+    ///
+    /// With strict (or canonical) checks, the terminator is required, and a
+    /// message without one is `CobsError::Truncated`, as it is for
+    /// [`DecodeOptions::decode_buf`].
+    pub fn decode_in_place(&self, bytes: &mut [u8]) -> Result<usize, CobsError> {
+        self.decode_in_place_raw(bytes)
+            .map(|(decoded_len, _)| decoded_len)
+            .map_err(|e| e.kind)
+    }
+
+    /// Decodes a message in-place, like [`decode_in_place_with_consumed`],
+    /// using these options.
+    pub fn decode_in_place_with_consumed(&self, bytes: &mut [u8]) -> Result<Decoded, CobsError> {
+        self.decode_in_place_detailed(bytes).map_err(|e| e.kind)
+    }
+
+    /// Decodes a message in-place, like
+    /// [`DecodeOptions::decode_in_place_with_consumed`], but on failure,
+    /// reports where in the input the problem was found, as
+    /// [`DecodeOptions::decode_buf_detailed`] does.
+    pub fn decode_in_place_detailed(&self, bytes: &mut [u8]) -> Result<Decoded, DecodeError> {
+        match self.decode_in_place_raw(bytes)? {
+            (decoded_len, Some(consumed)) => Ok(Decoded {
+                decoded_len,
+                consumed,
+            }),
+            (decoded_len, None) => Err(DecodeError {
+                kind: CobsError::Truncated,
+                offset: bytes.len(),
+                code: None,
+                output_len: decoded_len,
+            }),
+        }
+    }
+
+    /// Picks the instance of `decode_in_place_inner` for these options.
+    fn decode_in_place_raw(
+        &self,
+        bytes: &mut [u8],
+    ) -> Result<(usize, Option<usize>), DecodeError> {
+        with_checks!(self, |STRICT, CANONICAL| {
+            decode_in_place_inner::<u8, STRICT, CANONICAL, ZERO>(bytes)
+        })
+    }
+
+    /// Checks a message without decoding it, like [`validate`], using these
+    /// options.
+    pub fn validate(&self, bytes: &[u8]) -> Result<FrameInfo, CobsError> {
+        self.validate_detailed(bytes).map_err(|e| e.kind)
+    }
+
+    /// Checks a message without decoding it, like [`DecodeOptions::validate`],
+    /// but on failure, reports where in the input the problem was found. The
+    /// details are the same as [`DecodeOptions::decode_buf_detailed`] would
+    /// report, given a large enough output buffer.
+    pub fn validate_detailed(&self, bytes: &[u8]) -> Result<FrameInfo, DecodeError> {
+        scan_runs::<u8, ZERO>(bytes, ScanStart::default(), *self)
+    }
+
+    /// Decodes a message from a circular buffer, like [`decode_ring_buf`],
+    /// using these options.
+    pub fn decode_ring_buf(
+        &self,
+        halves: (&[u8], &[u8]),
+        output: &mut [u8],
+    ) -> Result<RingDecoded, CobsError> {
+        self.decode_ring_buf_detailed(halves, output).map_err(|e| e.kind)
+    }
+
+    /// Decodes a message from a circular buffer, like
+    /// [`DecodeOptions::decode_ring_buf`], but on failure, reports where in
+    /// the input the problem was found, as [`DecodeOptions::decode_buf_detailed`]
+    /// does. Offsets count from the start of the first half.
+    pub fn decode_ring_buf_detailed(
+        &self,
+        halves: (&[u8], &[u8]),
+        output: &mut [u8],
+    ) -> Result<RingDecoded, DecodeError> {
+        with_checks!(self, |STRICT, CANONICAL| {
+            decode_ring_buf_core::<STRICT, CANONICAL>(halves, output)
+        })
+    }
+}
+
+/// Incremental COBS decoder, for processing a stream of bytes as they arrive.
+///
+/// Feed the decoder a byte at a time with [`Decoder::advance`], or a chunk at a
+/// time with [`Decoder::feed`].
+///
+/// Unlike `decode_buf`, the decoder always checks for zeroes in the middle of a
+/// message, and reports them as `CobsError::Corrupt`. (See
+/// [`DecodeOptions::strict`] to get the same behavior elsewhere.)
+///
+/// By default, a `Decoder` handles a single frame: once it reaches the end of
+/// the frame, it can't be fed any more, and if it hits an error, it should be
+/// thrown away. Alternatively, with [`Decoder::with_resync`], the decoder
+/// handles errors and frame boundaries itself, and can be fed a continuous
+/// stream.
+///
+/// The type parameter `S` is the symbol type; decoders for symbols other than
+/// bytes come from [`symbol::decoder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoder<const DELIM: u8 = ZERO, S = u8> {
+    state: DState,
+    /// Whether we recover automatically at the end of a frame, or on error.
+    resync: bool,
+    /// Number of bytes dropped while hunting for a frame boundary.
+    skipped: usize,
+    /// Limit on the decoded length of a frame, if any.
+    max_len: Option<usize>,
+    /// Decoded length of the current frame so far.
+    len: usize,
+    /// Number of bytes of the current frame consumed so far.
+    offset: usize,
+    /// Length byte of the current run, if we're in one.
+    code: Option<S>,
+    _symbol: PhantomData<S>,
+}
+
+impl Decoder {
+    /// Creates a `Decoder` that will process a single frame. This is the same
+    /// as `Decoder::default()`.
+    ///
+    /// This decodes standard COBS, delimited by zero. For other delimiters,
+    /// see [`Cobs::decoder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<const DELIM: u8, S> Decoder<DELIM, S> {
+    /// Creates a decoder in its initial state, for any delimiter and symbol
+    /// type.
+    pub(crate) const fn empty() -> Self {
+        Self {
+            state: DState::Start,
+            resync: false,
+            skipped: 0,
+            max_len: None,
+            len: 0,
+            offset: 0,
+            code: None,
+            _symbol: PhantomData,
+        }
+    }
+}
+
+impl<const DELIM: u8, S: Symbol> Decoder<DELIM, S> {
+    /// Enables or disables automatic resynchronization.
+    ///
+    /// With this enabled, the decoder can be used on a continuous stream of
+    /// frames:
+    ///
+    /// - After it reports `DecodeStatus::Done`, the next byte starts a new
+    ///   frame, as though you had called [`Decoder::reset`].
+    ///
+    /// - After it reports an error, it drops input until the next frame
+    ///   boundary (if the error wasn't found _at_ a frame boundary), and then
+    ///   starts a new frame. Dropped bytes are counted, see
+    ///   [`Decoder::skipped`].
+    ///
+    /// ```
+    /// let mut decoder = corncobs::Decoder::new().with_resync(true);
+    /// let mut output = [0; 8];
+    /// let mut input: &[u8] = &[0x04, 0x11, 0x00, 0x02, 0x22, 0x00];
+    ///
+    /// // The first frame is cut short...
+    /// let e = decoder.feed(input, &mut output).unwrap_err();
+    /// assert_eq!(e.error, corncobs::CobsError::Corrupt);
+    /// input = &input[e.consumed..];
+    ///
+    /// // ...but the decoder picks up with the next.
+    /// let r = decoder.feed(input, &mut output).unwrap();
+    /// assert!(r.frame_done);
+    /// assert_eq!(&output[..r.produced], [0x22]);
+    /// ```
+    pub fn with_resync(mut self, enabled: bool) -> Self {
+        self.resync = enabled;
+        self
+    }
+
+    /// Sets a limit on the decoded length of a frame.
+    ///
+    /// If a frame grows past `max_len` bytes, the decoder reports
+    /// `CobsError::TooLong` right away, rather than waiting for the end of the
+    /// frame. This keeps a stuck or noisy line from running you out of buffer.
+    /// With resynchronization enabled, the decoder then drops the rest of the
+    /// frame.
+    ///
+    /// ```
+    /// let mut decoder = corncobs::Decoder::new().with_max_len(2);
+    /// let mut output = [0; 8];
+    /// let e = decoder.feed(&[0x05, 0x11, 0x22, 0x33, 0x44, 0x00], &mut output).unwrap_err();
+    /// assert_eq!(e.error, corncobs::CobsError::TooLong);
+    /// assert_eq!(e.consumed, 4);
+    /// ```
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Discards any partially decoded frame, and prepares to decode a new
+    /// one. The byte counter used by [`Decoder::skipped`] is not affected.
+    pub fn reset(&mut self) {
+        self.state = DState::Start;
+        self.start_frame();
+    }
+
+    /// Discards any partially decoded frame, and drops input until the next
+    /// frame boundary (zero byte). This is useful if you've started receiving
+    /// in the middle of a stream, or if a higher-level check (such as a CRC)
+    /// tells you something is wrong with the current frame.
+    pub fn hunt(&mut self) {
+        self.state = DState::Hunting;
+        self.start_frame();
+    }
+
+    /// Checks whether the decoder is dropping input, looking for a frame
+    /// boundary.
+    pub fn is_hunting(&self) -> bool {
+        self.state == DState::Hunting
+    }
+
+    /// Returns the total number of bytes dropped while hunting for frame
+    /// boundaries. This doesn't include the zero bytes that end each hunt.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn advance(&mut self, byte: S) -> Result<DecodeStatus<S>, CobsError> {
+        self.advance_detailed(byte).map_err(|e| e.kind)
+    }
+
+    /// Processes a single byte, like [`Decoder::advance`], but on failure,
+    /// reports where in the frame the problem was found, as
+    /// [`DecodeOptions::decode_buf_detailed`] does. Offsets count from the
+    /// first byte of the frame, not counting any bytes dropped while hunting.
+    ///
+    /// ```
+    /// let mut decoder = corncobs::Decoder::new();
+    /// for &b in &[0x02, 0x11, 0x03, 0x22] {
+    ///     decoder.advance_detailed(b).unwrap();
+    /// }
+    /// let e = decoder.advance_detailed(0x00).unwrap_err();
+    /// assert_eq!(e.kind, corncobs::CobsError::Corrupt);
+    /// assert_eq!((e.offset, e.code, e.output_len), (4, Some(0x03), 3));
+    /// ```
+    pub fn advance_detailed(&mut self, byte: S) -> Result<DecodeStatus<S>, DecodeError<S>> {
+        let offset = self.offset;
+        let hunting = self.state == DState::Hunting;
+        let status = match self.step(byte) {
+            Ok(status) => status,
+            Err(kind) => {
+                let e = self.error_at(kind, offset);
+                self.start_frame();
+                return Err(e);
+            }
+        };
+        self.offset += 1;
+        match status {
+            DecodeStatus::Append(_) => {
+                if Some(self.len) == self.max_len {
+                    let e = self.error_at(CobsError::TooLong, offset);
+                    if self.resync {
+                        self.state = DState::Hunting;
+                    }
+                    self.start_frame();
+                    return Err(e);
+                }
+                self.len += 1;
+            }
+            DecodeStatus::Done => self.start_frame(),
+            // Bytes dropped while hunting, and the zero that ends the hunt,
+            // aren't part of any frame.
+            DecodeStatus::Pending if hunting => self.start_frame(),
+            DecodeStatus::Pending => (),
+        }
+        Ok(status)
+    }
+
+    /// Describes an error found at `offset` in the current frame.
+    fn error_at(&self, kind: CobsError, offset: usize) -> DecodeError<S> {
+        DecodeError {
+            kind,
+            offset,
+            code: self.code,
+            output_len: self.len,
+        }
+    }
+
+    /// Describes an error found at the next byte of the current frame.
+    fn error(&self, kind: CobsError) -> DecodeError<S> {
+        self.error_at(kind, self.offset)
+    }
+
+    /// Forgets the progress of the current frame.
+    fn start_frame(&mut self) {
+        self.len = 0;
+        self.offset = 0;
+        self.code = None;
+    }
+
+    /// Advances the state machine, without enforcing `max_len`.
+    fn step(&mut self, byte: S) -> Result<DecodeStatus<S>, CobsError> {
+        let delim = S::from(DELIM);
+        match self.state {
+            DState::Start | DState::Tween(true) => {
+                if let Some(count) = (byte ^ delim).decode_len() {
+                    self.code = Some(byte);
+                    if let Some(count2) = count.checked_sub(1) {
+                        self.state = DState::Literal(count2, count == S::MAX_RUN);
+                    } else {
+                        self.state = DState::Tween(false);
+                    }
+                    Ok(DecodeStatus::Pending)
+                } else {
                     self.state = DState::Done;
                     Ok(DecodeStatus::Done)
                 }
             }
             DState::Tween(false) => {
-                if let Some(count) = byte.checked_sub(1) {
+                if let Some(count) = (byte ^ delim).decode_len() {
+                    self.code = Some(byte);
                     if let Some(count2) = count.checked_sub(1) {
-                        self.state = DState::Literal(count2, byte == 0xFF);
+                        self.state = DState::Literal(count2, count == S::MAX_RUN);
                     } else {
                         self.state = DState::Tween(false);
                     }
-                    Ok(DecodeStatus::Append(ZERO))
+                    Ok(DecodeStatus::Append(delim))
                 } else {
                     self.state = DState::Done;
                     Ok(DecodeStatus::Done)
                 }
             }
             DState::Literal(n, omit_zero) => {
-                if byte == ZERO {
+                if byte == delim {
+                    if self.resync {
+                        // The zero that broke this frame is the boundary
+                        // before the next one.
+                        self.state = DState::Start;
+                    }
                     Err(CobsError::Corrupt)
                 } else {
                     if let Some(next_n) = n.checked_sub(1) {
@@ -591,8 +2515,149 @@ impl Decoder {
                     Ok(DecodeStatus::Append(byte))
                 }
             }
-            DState::Done => panic!(),
+            DState::Hunting => {
+                if byte == delim {
+                    self.state = DState::Start;
+                } else {
+                    self.skipped = self.skipped.saturating_add(1);
+                }
+                Ok(DecodeStatus::Pending)
+            }
+            DState::Done => {
+                if self.resync {
+                    self.state = DState::Start;
+                    self.step(byte)
+                } else {
+                    panic!()
+                }
+            }
+        }
+    }
+
+    /// Decodes a chunk of input into `output`, continuing from wherever the
+    /// last call left off.
+    ///
+    /// This is the bulk equivalent of calling `advance` on each byte of
+    /// `input`, but it's much faster, since it moves runs of literal bytes as
+    /// blocks.
+    ///
+    /// Decoding stops when the end of the frame is reached (in which case
+    /// `frame_done` is set in the result, and any input past the terminator is
+    /// left unconsumed), when `input` runs out, or when `output` fills up. The
+    /// result says how much of `input` was consumed, and how much of `output`
+    /// was produced, so that you can pick up where you left off.
+    ///
+    /// ```
+    /// let mut decoder = corncobs::Decoder::default();
+    /// let mut output = [0; 8];
+    ///
+    /// let r = decoder.feed(&[0x03, 0x11], &mut output).unwrap();
+    /// assert_eq!((r.consumed, r.produced, r.frame_done), (2, 1, false));
+    ///
+    /// let r = decoder.feed(&[0x22, 0x02, 0x33, 0x00, 0xAA], &mut output[1..]).unwrap();
+    /// assert_eq!((r.consumed, r.produced, r.frame_done), (4, 3, true));
+    /// assert_eq!(&output[..4], [0x11, 0x22, 0x00, 0x33]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// As with `advance`, a zero in the middle of a run produces
+    /// `CobsError::Corrupt`, and exceeding the limit set by
+    /// [`Decoder::with_max_len`] produces `CobsError::TooLong`. The returned
+    /// [`FeedError`] says how much input was consumed, up to and including the
+    /// byte that caused the error, so that a decoder using
+    /// [`Decoder::with_resync`] can carry on from there. It also says where in
+    /// the frame the error was, as [`Decoder::advance_detailed`] does.
+    ///
+    /// # Panics
+    ///
+    /// If called after the end of the frame has been reached, as with
+    /// `advance`, unless resynchronization is enabled.
+    pub fn feed(&mut self, input: &[S], output: &mut [S]) -> Result<FeedResult, FeedError<S>> {
+        let delim = S::from(DELIM);
+        let mut inpos = 0;
+        let mut outpos = 0;
+        while inpos < input.len() {
+            match self.state {
+                DState::Literal(n, omit_zero) => {
+                    if outpos == output.len() {
+                        // Output is full.
+                        break;
+                    }
+                    // Move as much of the run as we can in one go. If there's
+                    // a zero in the way, or we've hit the length limit, this
+                    // stops short, and we let `advance` deal with it below.
+                    let run_left = n + 1;
+                    let room = self.max_len.map_or(usize::MAX, |m| m - self.len);
+                    let k = copy_run(
+                        &input[inpos..],
+                        &mut output[outpos..],
+                        run_left.min(room),
+                        delim,
+                    );
+                    inpos += k;
+                    outpos += k;
+                    self.len += k;
+                    self.offset += k;
+                    if k == 0 {
+                        let byte = input[inpos];
+                        inpos += 1;
+                        let e = self.advance_detailed(byte).unwrap_err();
+                        return Err(FeedError::new(e, inpos, outpos));
+                    }
+                    self.state = if let Some(n) = (run_left - k).checked_sub(1) {
+                        DState::Literal(n, omit_zero)
+                    } else {
+                        DState::Tween(omit_zero)
+                    };
+                }
+                DState::Hunting => {
+                    // Skip everything up to the next zero in one go.
+                    let rest = &input[inpos..];
+                    if let Some(i) = rest.iter().position(|&b| b == delim) {
+                        self.skipped = self.skipped.saturating_add(i);
+                        self.state = DState::Start;
+                        inpos += i + 1;
+                    } else {
+                        self.skipped = self.skipped.saturating_add(rest.len());
+                        inpos = input.len();
+                    }
+                }
+                _ => {
+                    // Length bytes are rare enough that we just use
+                    // `advance`. It may want to append a zero, so make sure
+                    // there's room.
+                    let byte = input[inpos];
+                    if outpos == output.len()
+                        && self.state == DState::Tween(false)
+                        && byte != delim
+                    {
+                        break;
+                    }
+                    inpos += 1;
+                    match self.advance_detailed(byte) {
+                        Ok(DecodeStatus::Append(b)) => {
+                            output[outpos] = b;
+                            outpos += 1;
+                        }
+                        Ok(DecodeStatus::Pending) => (),
+                        Ok(DecodeStatus::Done) => {
+                            return Ok(FeedResult {
+                                consumed: inpos,
+                                produced: outpos,
+                                frame_done: true,
+                            });
+                        }
+                        Err(e) => return Err(FeedError::new(e, inpos, outpos)),
+                    }
+                }
+            }
         }
+        Ok(FeedResult {
+            consumed: inpos,
+            produced: outpos,
+            frame_done: false,
+        })
     }
 
     pub fn is_done(&self) -> bool {
@@ -600,27 +2665,121 @@ impl Decoder {
     }
 }
 
+/// Progress report from [`Decoder::feed`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FeedResult {
+    /// Number of bytes of input consumed.
+    pub consumed: usize,
+    /// Number of bytes of output produced.
+    pub produced: usize,
+    /// Whether the end of the frame was reached.
+    pub frame_done: bool,
+}
+
+/// Error report from [`Decoder::feed`].
+///
+/// `consumed` and `produced` count from the start of the call to `feed`, while
+/// `offset` and `output_len` count from the start of the frame, which may have
+/// been fed in earlier calls. The type parameter is the symbol type, as for
+/// [`DecodeError`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FeedError<S = u8> {
+    /// What went wrong.
+    pub error: CobsError,
+    /// Number of bytes of input consumed, including the byte that caused the
+    /// error.
+    pub consumed: usize,
+    /// Number of bytes of output produced before the error was found.
+    pub produced: usize,
+    /// Offset in the frame of the byte that caused the error.
+    pub offset: usize,
+    /// Length byte of the run in which the problem was found, if it was in
+    /// one.
+    pub code: Option<S>,
+    /// Number of bytes of the frame that were decoded before the problem was
+    /// found.
+    pub output_len: usize,
+}
+
+impl<S> FeedError<S> {
+    /// Adds the progress through the current call to the details of an error.
+    pub(crate) fn new(e: DecodeError<S>, consumed: usize, produced: usize) -> Self {
+        Self {
+            error: e.kind,
+            consumed,
+            produced,
+            offset: e.offset,
+            code: e.code,
+            output_len: e.output_len,
+        }
+    }
+}
+
+impl<S: core::fmt::LowerHex> core::fmt::Display for FeedError<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let e = DecodeError {
+            kind: self.error,
+            offset: self.offset,
+            code: self.code.as_ref(),
+            output_len: self.output_len,
+        };
+        core::fmt::Display::fmt(&e, f)
+    }
+}
+
+impl<S: core::fmt::Debug + core::fmt::LowerHex> core::error::Error for FeedError<S> {}
+
+impl<S> From<FeedError<S>> for CobsError {
+    fn from(e: FeedError<S>) -> Self {
+        e.error
+    }
+}
+
+impl<S> From<FeedError<S>> for DecodeError<S> {
+    fn from(e: FeedError<S>) -> Self {
+        Self {
+            kind: e.error,
+            offset: e.offset,
+            code: e.code,
+            output_len: e.output_len,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum DState {
     Start,
     Tween(bool),
-    Literal(u8, bool),
+    Literal(usize, bool),
+    /// Dropping bytes until we see a zero.
+    Hunting,
     Done,
 }
 
-impl Default for DState {
-    fn default() -> Self {
-        Self::Start
-    }
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum DecodeStatus {
+pub enum DecodeStatus<S = u8> {
     Pending,
-    Append(u8),
+    Append(S),
     Done,
 }
 
+/// Copies the start of a run from `input` to `output`, stopping after `len`
+/// symbols, when either slice runs out, or at the first `delim` in `input`,
+/// which is left uncopied. Returns the number of symbols copied.
+///
+/// This is the fast path for the streaming decoders, which use it to move
+/// runs as blocks.
+#[inline(always)]
+fn copy_run<S: Symbol>(input: &[S], output: &mut [S], len: usize, delim: S) -> usize {
+    let len = len.min(input.len()).min(output.len());
+    let k = input[..len]
+        .iter()
+        .position(|&b| b == delim)
+        .unwrap_or(len);
+    output[..k].copy_from_slice(&input[..k]);
+    k
+}
+
 // Tests for private bits; test fixtures require std, unfortunately, so you have
 // to run these explicitly with `cargo test --features std`. Most of the API
 // tests are broken out into an integration test.
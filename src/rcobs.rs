@@ -0,0 +1,361 @@
+//! Reverse COBS (rCOBS): a variant where each length byte comes _after_ its
+//! run, rather than before it.
+//!
+//! A standard COBS encoder can't write a run's length byte until it has seen
+//! the end of the run, so it has to either look ahead through the input or
+//! hold on to up to 254 bytes of output (like [`Encoder`](crate::Encoder)
+//! does). Putting the length byte at the end of the run instead means that the
+//! encoder can pass each data byte straight through as it arrives, and only
+//! needs to count: the encoders here keep no buffer at all, which makes them a
+//! good fit for things like a UART transmit interrupt.
+//!
+//! The price is paid by the decoder, which has to start at the terminator and
+//! work backwards, so it needs the whole message in memory. Otherwise, the
+//! format is the same as standard COBS: the runs are the same, the length
+//! bytes have the same values, and messages are terminated by a zero. In
+//! particular, the encoded size is the same, so
+//! [`max_encoded_len`](crate::max_encoded_len) works here too.
+//!
+//! ```
+//! use corncobs::rcobs;
+//!
+//! let msg = [0x11, 0x22, 0x00, 0x33];
+//! let mut encoded = [0; corncobs::max_encoded_len(4)];
+//! let n = rcobs::encode_buf(&msg, &mut encoded);
+//! // Standard COBS would produce 03 11 22 02 33 00.
+//! assert_eq!(&encoded[..n], [0x11, 0x22, 0x03, 0x33, 0x02, 0x00]);
+//!
+//! let mut decoded = [0; 4];
+//! assert_eq!(rcobs::decode_buf(&encoded[..n], &mut decoded), Ok(4));
+//! assert_eq!(decoded, msg);
+//! ```
+
+use crate::{encode_len, take_run, CobsError, Decoded, Sink, MAX_RUN, ZERO};
+
+/// Encodes the message `bytes` into the buffer `output` using rCOBS. Returns
+/// the number of bytes used in `output`, which also happens to be the index of
+/// the first zero byte.
+///
+/// This works like [`encode_buf`](crate::encode_buf), and has the same
+/// requirements for the size of `output`.
+///
+/// # Panics
+///
+/// If `output` is too small to contain the encoded form of `input`, which is
+/// `max_encoded_len(bytes.len())` bytes worst-case.
+pub fn encode_buf(bytes: &[u8], output: &mut [u8]) -> usize {
+    debug_assert!(output.len() >= crate::max_encoded_len(bytes.len()));
+
+    let mut outpos = 0;
+    let mut rest = Some(bytes);
+    while let Some(bytes) = rest {
+        let (run, new_rest) = take_run(bytes);
+        output[outpos..outpos + run.len()].copy_from_slice(run);
+        output[outpos + run.len()] = encode_len(run.len());
+        outpos += run.len() + 1;
+        rest = new_rest;
+    }
+    output[outpos] = ZERO;
+    outpos + 1
+}
+
+/// Encodes `bytes` into the vector `output` using rCOBS. This is a
+/// convenience for cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn encode(bytes: &[u8], output: &mut Vec<u8>) {
+    let offset = output.len();
+    output.resize(offset + crate::max_encoded_len(bytes.len()), 0);
+    let actual_len = encode_buf(bytes, &mut output[offset..]);
+    output.truncate(offset + actual_len);
+}
+
+/// Encodes `bytes` using rCOBS, yielding individual encoded bytes through an
+/// iterator.
+///
+/// Unlike [`encode_iter`](crate::encode_iter), this never looks ahead in
+/// `bytes`: each byte of output depends only on the input up to that point.
+///
+/// ```
+/// let encoded: Vec<u8> = corncobs::rcobs::encode_iter(&[0x11, 0x00]).collect();
+/// assert_eq!(encoded, [0x11, 0x02, 0x01, 0x00]);
+/// ```
+pub fn encode_iter(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let mut rest = Some(bytes);
+    // Length of the run so far.
+    let mut len = 0;
+    // Whether the run so far has already been written out, because it was
+    // maximal length or because it ended the message.
+    let mut flushed = false;
+    core::iter::from_fn(move || {
+        let bytes = rest?;
+        if len == MAX_RUN {
+            len = 0;
+            flushed = true;
+            return Some(encode_len(MAX_RUN));
+        }
+        if let Some((&b, tail)) = bytes.split_first() {
+            rest = Some(tail);
+            flushed = false;
+            if b == ZERO {
+                let code = encode_len(len);
+                len = 0;
+                Some(code)
+            } else {
+                len += 1;
+                Some(b)
+            }
+        } else if !flushed {
+            // Like `encode_buf`, we omit the final empty run if it follows a
+            // maximal run.
+            let code = encode_len(len);
+            len = 0;
+            flushed = true;
+            Some(code)
+        } else {
+            rest = None;
+            Some(ZERO)
+        }
+    })
+}
+
+/// Incremental rCOBS encoder.
+///
+/// This has the same interface as the crate's [`Encoder`](crate::Encoder),
+/// but since rCOBS doesn't need to know how long a run is before writing it,
+/// there's no internal buffer: input is passed on to the [`Sink`] as soon as
+/// it arrives (in blocks, when using [`Encoder::push`]), with length bytes
+/// added in between.
+///
+/// ```
+/// let mut out = vec![];
+/// let mut enc = corncobs::rcobs::Encoder::new(|b: &[u8]| out.extend_from_slice(b));
+/// enc.push(&[0x11, 0x22]);
+/// enc.push_byte(0x00);
+/// enc.push(&[0x33]);
+/// enc.finish();
+///
+/// assert_eq!(out, [0x11, 0x22, 0x03, 0x33, 0x02, 0x00]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Encoder<S> {
+    sink: S,
+    /// Number of data bytes written in the current run.
+    len: usize,
+    /// Set when the most recently ended run was maximal length and nothing
+    /// has arrived since, as in the crate's `Encoder`.
+    after_max: bool,
+}
+
+impl<S: Sink> Encoder<S> {
+    /// Creates an `Encoder` that will deliver output to `sink`.
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            len: 0,
+            after_max: false,
+        }
+    }
+
+    /// Adds a single byte to the message.
+    pub fn push_byte(&mut self, byte: u8) {
+        if byte == ZERO {
+            self.end_run();
+        } else {
+            self.sink.accept(&[byte]);
+            self.len += 1;
+            if self.len == MAX_RUN {
+                self.end_run();
+                self.after_max = true;
+            }
+        }
+    }
+
+    /// Adds `bytes` to the message.
+    ///
+    /// This hands non-zero bytes to the sink in blocks, which is usually
+    /// faster than calling `push_byte` in a loop.
+    pub fn push(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let max_len = usize::min(bytes.len(), MAX_RUN - self.len);
+            let run_len = bytes[..max_len]
+                .iter()
+                .position(|&b| b == ZERO)
+                .unwrap_or(max_len);
+            let (run, rest) = bytes.split_at(run_len);
+            if !run.is_empty() {
+                self.sink.accept(run);
+            }
+            self.len += run_len;
+            bytes = rest;
+
+            if self.len == MAX_RUN {
+                self.end_run();
+                self.after_max = true;
+            } else if let Some((_zero, rest)) = bytes.split_first() {
+                debug_assert_eq!(_zero, &ZERO);
+                self.end_run();
+                bytes = rest;
+            }
+        }
+    }
+
+    /// Ends the current message, writing the final length byte and the
+    /// terminating zero to the sink. The `Encoder` can then be used to encode
+    /// another message.
+    pub fn end_frame(&mut self) {
+        if self.len != 0 || !self.after_max {
+            self.end_run();
+        }
+        self.sink.accept(&[ZERO]);
+        self.after_max = false;
+    }
+
+    /// Ends the current message (as with [`Encoder::end_frame`]) and drops the
+    /// `Encoder`.
+    pub fn finish(mut self) {
+        self.end_frame();
+    }
+
+    /// Gets a reference to the sink.
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Gets a mutable reference to the sink.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the sink. Any partial message that has not been ended with
+    /// [`Encoder::end_frame`] will be left incomplete.
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// Writes the length byte for the current run, and starts a new one.
+    fn end_run(&mut self) {
+        self.sink.accept(&[encode_len(self.len)]);
+        self.len = 0;
+        self.after_max = false;
+    }
+}
+
+/// Decodes rCOBS input from `bytes` into `output` starting at index 0. Returns
+/// the number of bytes used in `output`.
+///
+/// `bytes` must contain the whole message, up to and including the
+/// terminator, since that's where decoding starts. Anything after the
+/// terminator is ignored. As with [`decode_buf`](crate::decode_buf), `output`
+/// is large enough if it's at least `bytes.len()` bytes long, and if it's too
+/// small, you'll get `CobsError::OutputTooSmall`.
+///
+/// If the length bytes lead back past the start of `bytes`, the start of the
+/// message is missing, and the result is `CobsError::Truncated`.
+pub fn decode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+    decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+}
+
+/// Decodes rCOBS input from `bytes` into `output` starting at index 0, and
+/// also reports how many bytes of `bytes` made up the message, including the
+/// terminator, like
+/// [`decode_buf_with_consumed`](crate::decode_buf_with_consumed).
+pub fn decode_buf_with_consumed(bytes: &[u8], output: &mut [u8]) -> Result<Decoded, CobsError> {
+    let (end, decoded_len) = measure(bytes)?;
+    if output.len() < decoded_len {
+        return Err(CobsError::OutputTooSmall {
+            needed: decoded_len,
+        });
+    }
+
+    let mut inpos = end;
+    let mut outpos = decoded_len;
+    let mut last = true;
+    while inpos > 0 {
+        let (start, n) = run_before(bytes, inpos)?;
+        if !last && n != MAX_RUN {
+            outpos -= 1;
+            output[outpos] = ZERO;
+        }
+        outpos -= n;
+        output[outpos..outpos + n].copy_from_slice(&bytes[start..start + n]);
+        inpos = start;
+        last = false;
+    }
+    debug_assert_eq!(outpos, 0);
+    Ok(Decoded {
+        decoded_len,
+        consumed: end + 1,
+    })
+}
+
+/// Decodes rCOBS input from `bytes` into a vector. This is a convenience for
+/// cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
+    let offset = output.len();
+    output.resize(offset + bytes.len(), 0);
+    let actual_len = decode_buf(bytes, &mut output[offset..])?;
+    output.truncate(offset + actual_len);
+    Ok(())
+}
+
+/// Decodes an rCOBS message in-place, like
+/// [`decode_in_place`](crate::decode_in_place). Returns the number of decoded
+/// bytes, which are deposited at the start of `bytes`.
+///
+/// Unlike `decode_in_place`, this requires the terminator to be present, and
+/// returns `CobsError::Truncated` if it isn't.
+pub fn decode_in_place(bytes: &mut [u8]) -> Result<usize, CobsError> {
+    let (end, decoded_len) = measure(bytes)?;
+
+    // Working backwards, we have to write behind the position we're reading
+    // from, which means at the end of the message. Each run moves towards the
+    // end by the size of its length byte, less any zero it turns into.
+    let mut inpos = end;
+    let mut outpos = end;
+    let mut last = true;
+    while inpos > 0 {
+        let (start, n) = run_before(bytes, inpos)?;
+        if !last && n != MAX_RUN {
+            outpos -= 1;
+            bytes[outpos] = ZERO;
+        }
+        outpos -= n;
+        bytes.copy_within(start..start + n, outpos);
+        inpos = start;
+        last = false;
+    }
+    // Then move the result to the front.
+    bytes.copy_within(outpos..end, 0);
+    Ok(decoded_len)
+}
+
+/// Finds the terminator in `bytes`, and checks the message in front of it.
+/// Returns the index of the terminator and the decoded length of the message.
+fn measure(bytes: &[u8]) -> Result<(usize, usize), CobsError> {
+    let end = bytes
+        .iter()
+        .position(|&b| b == ZERO)
+        .ok_or(CobsError::Truncated)?;
+    let mut inpos = end;
+    let mut decoded_len = 0;
+    let mut last = true;
+    while inpos > 0 {
+        let (start, n) = run_before(bytes, inpos)?;
+        // Every run but the last is followed by a zero, unless it's maximal
+        // length.
+        decoded_len += n + usize::from(!last && n != MAX_RUN);
+        inpos = start;
+        last = false;
+    }
+    Ok((end, decoded_len))
+}
+
+/// Reads the length byte at `bytes[end - 1]`, and returns the index where the
+/// run in front of it starts, along with the run's length.
+fn run_before(bytes: &[u8], end: usize) -> Result<(usize, usize), CobsError> {
+    // We only ever look in front of the terminator, so this isn't zero.
+    let n = usize::from(bytes[end - 1]) - 1;
+    let start = (end - 1).checked_sub(n).ok_or(CobsError::Truncated)?;
+    Ok((start, n))
+}
@@ -0,0 +1,231 @@
+//! Frame accumulators built on `Decoder`.
+
+use crate::{CobsError, DecodeError, Decoder};
+
+/// Receives a stream of encoded data in arbitrary chunks, and collects it into
+/// decoded frames, using an internal buffer of `N` bytes.
+///
+/// This is the component that sits between, say, a UART receive interrupt and
+/// the code that handles messages. You feed it whatever bytes have arrived
+/// using [`FrameReceiver::push`] (or [`FrameReceiver::push_all`]), and it gives
+/// back each frame as it's completed, borrowed from its buffer.
+///
+/// Problems with individual frames are reported as errors, after which the
+/// receiver picks up again at the next frame. Errors are [`DecodeError`]s, with
+/// offsets counted from the start of the frame, as for
+/// [`Decoder::advance_detailed`](crate::Decoder::advance_detailed):
+///
+/// - A frame that decodes to more than `N` bytes is discarded, and reported as
+///   `CobsError::OutputTooSmall` with its full decoded size. The offset is
+///   that of the first byte that didn't fit.
+/// - A frame that's interrupted by a zero in the middle of a run (which is what
+///   happens if bytes are lost, or you tune in partway through a frame) is
+///   reported as `CobsError::Corrupt`.
+/// - If you've set a limit with [`FrameReceiver::with_max_len`], a frame that
+///   grows past it is reported as `CobsError::TooLong` as soon as that happens,
+///   and the rest of it is dropped.
+///
+/// A lone zero in the input is received as an empty frame, as it would be by
+/// `decode_buf`.
+///
+/// If you'd rather supply the buffer yourself, see [`FrameReceiverRef`].
+///
+/// ```
+/// let mut rx = corncobs::FrameReceiver::<16>::new();
+/// let mut frames = vec![];
+/// for chunk in [&[0x03, 0x11][..], &[0x22, 0x00, 0x02], &[0x33, 0x00]] {
+///     rx.push_all(chunk, |frame| frames.push(frame.unwrap().to_vec()));
+/// }
+/// assert_eq!(frames, [&[0x11, 0x22][..], &[0x33]]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameReceiver<const N: usize> {
+    buf: [u8; N],
+    state: ReceiverState,
+}
+
+impl<const N: usize> FrameReceiver<N> {
+    /// Creates a `FrameReceiver` with an empty buffer.
+    pub fn new() -> Self {
+        Self {
+            buf: [0; N],
+            state: ReceiverState::default(),
+        }
+    }
+
+    /// Limits frames to `max_len` decoded bytes, which may be less than `N`.
+    /// See [`Decoder::with_max_len`](crate::Decoder::with_max_len).
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.state.decoder = self.state.decoder.with_max_len(max_len);
+        self
+    }
+
+    /// Processes `input` until either it runs out, or a frame is completed.
+    ///
+    /// Returns the number of bytes of `input` consumed, and the completed
+    /// frame (or error), if any. If there's a frame, there may be input left
+    /// over, which you'll want to `push` next.
+    pub fn push(&mut self, input: &[u8]) -> (usize, Option<Result<&[u8], DecodeError>>) {
+        self.state.push(&mut self.buf, input)
+    }
+
+    /// Processes all of `input`, calling `f` with each frame (or error) as it's
+    /// completed.
+    pub fn push_all(&mut self, input: &[u8], f: impl FnMut(Result<&[u8], DecodeError>)) {
+        self.state.push_all(&mut self.buf, input, f)
+    }
+
+    /// Discards any partially received frame.
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+}
+
+impl<const N: usize> Default for FrameReceiver<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`FrameReceiver`] that uses a buffer you provide, rather than owning one.
+///
+/// This behaves exactly like `FrameReceiver`, but the maximum frame size is the
+/// length of the buffer.
+#[derive(Debug)]
+pub struct FrameReceiverRef<'a> {
+    buf: &'a mut [u8],
+    state: ReceiverState,
+}
+
+impl<'a> FrameReceiverRef<'a> {
+    /// Creates a `FrameReceiverRef` that will collect frames in `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            state: ReceiverState::default(),
+        }
+    }
+
+    /// Limits frames to `max_len` decoded bytes. See
+    /// [`FrameReceiver::with_max_len`].
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.state.decoder = self.state.decoder.with_max_len(max_len);
+        self
+    }
+
+    /// Processes `input` until either it runs out, or a frame is completed. See
+    /// [`FrameReceiver::push`].
+    pub fn push(&mut self, input: &[u8]) -> (usize, Option<Result<&[u8], DecodeError>>) {
+        self.state.push(self.buf, input)
+    }
+
+    /// Processes all of `input`, calling `f` with each frame (or error) as it's
+    /// completed.
+    pub fn push_all(&mut self, input: &[u8], f: impl FnMut(Result<&[u8], DecodeError>)) {
+        self.state.push_all(self.buf, input, f)
+    }
+
+    /// Discards any partially received frame.
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Returns the buffer.
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buf
+    }
+}
+
+/// The guts of a frame receiver, independent of how the buffer is stored.
+#[derive(Clone, Debug)]
+struct ReceiverState {
+    decoder: Decoder,
+    /// Decoded length of the current frame so far.
+    len: usize,
+    /// Set if the current frame has outgrown the buffer, to the error we'll
+    /// report. We keep decoding it, without storing the results, to find out
+    /// how big it is.
+    overflow: Option<DecodeError>,
+}
+
+impl Default for ReceiverState {
+    fn default() -> Self {
+        Self {
+            decoder: Decoder::new().with_resync(true),
+            len: 0,
+            overflow: None,
+        }
+    }
+}
+
+impl ReceiverState {
+    fn reset(&mut self) {
+        self.decoder.reset();
+        self.len = 0;
+        self.overflow = None;
+    }
+
+    fn push<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+        input: &[u8],
+    ) -> (usize, Option<Result<&'b [u8], DecodeError>>) {
+        let mut consumed = 0;
+        while consumed < input.len() {
+            let rest = &input[consumed..];
+            let mut scratch = [0; 16];
+            let out = if self.overflow.is_some() {
+                &mut scratch[..]
+            } else {
+                &mut buf[self.len..]
+            };
+            match self.decoder.feed(rest, out) {
+                Ok(r) => {
+                    consumed += r.consumed;
+                    self.len += r.produced;
+                    if r.frame_done {
+                        let len = self.len;
+                        self.len = 0;
+                        let result = match self.overflow.take() {
+                            Some(e) => Err(DecodeError {
+                                kind: CobsError::OutputTooSmall { needed: len },
+                                ..e
+                            }),
+                            None => Ok(&buf[..len]),
+                        };
+                        return (consumed, Some(result));
+                    }
+                    if consumed < input.len() && self.overflow.is_none() {
+                        // The decoder stopped early, so the buffer is full.
+                        let e = self.decoder.error(CobsError::OutputTooSmall { needed: 0 });
+                        self.overflow = Some(e);
+                    }
+                }
+                Err(e) => {
+                    // The decoder is set up to resync, so it's already
+                    // recovering; we just need to forget the damaged frame.
+                    consumed += e.consumed;
+                    self.len = 0;
+                    self.overflow = None;
+                    return (consumed, Some(Err(e.into())));
+                }
+            }
+        }
+        (consumed, None)
+    }
+
+    fn push_all(
+        &mut self,
+        buf: &mut [u8],
+        mut input: &[u8],
+        mut f: impl FnMut(Result<&[u8], DecodeError>),
+    ) {
+        while !input.is_empty() {
+            let (n, result) = self.push(buf, input);
+            input = &input[n..];
+            if let Some(result) = result {
+                f(result);
+            }
+        }
+    }
+}
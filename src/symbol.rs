@@ -0,0 +1,270 @@
+//! COBS over symbols wider than a byte.
+//!
+//! The rest of this crate works on bytes, but nothing about COBS requires
+//! that: the same algorithm works on any sequence of unsigned integers, with
+//! zero as the delimiter and the length "byte" being a whole symbol. With
+//! 16-bit symbols, a run can be up to 65534 symbols long, so the overhead is
+//! tiny, and data that naturally comes in 16-bit units (samples, or words
+//! from a 9-bit or 16-bit peripheral) can be encoded without converting it to
+//! bytes and back.
+//!
+//! The functions here are generic over the symbol type, which can be anything
+//! implementing [`Symbol`]; that includes `u8`, for which they're the same as
+//! the byte-oriented routines at the top of the crate (which have many more
+//! options).
+//!
+//! ```
+//! use corncobs::symbol;
+//!
+//! let samples: [u16; 4] = [0x1111, 0x0000, 0x2222, 0x3333];
+//! let mut encoded = [0; symbol::max_encoded_len::<u16>(4)];
+//! let n = symbol::encode_buf(&samples, &mut encoded);
+//! assert_eq!(&encoded[..n], [0x0002, 0x1111, 0x0003, 0x2222, 0x3333, 0x0000]);
+//!
+//! let mut decoded = [0; 4];
+//! assert_eq!(symbol::decode_buf(&encoded[..n], &mut decoded), Ok(4));
+//! assert_eq!(decoded, samples);
+//! ```
+//!
+//! Buffer sizes are counted in symbols, and come from this module's
+//! [`max_encoded_len`], [`min_encoded_len`] and [`max_decoded_len`], and their
+//! checked versions, which work like the crate's.
+
+use core::fmt::Debug;
+use core::ops::BitXor;
+
+use crate::{CobsError, Decoded, ZERO};
+
+/// A type that COBS can be applied to sequences of.
+///
+/// This is implemented for `u8` and `u16`. The length of a run is stored in a
+/// single symbol as `len + 1`, so runs can be up to `MAX_RUN` symbols long,
+/// where `MAX_RUN` is one less than the largest value of the type.
+///
+/// The trait is sealed, so it can't be implemented outside this crate; the
+/// decoders rely on the arithmetic above, which a foreign implementation could
+/// get wrong.
+///
+/// The crate's own encoders and decoders are written in terms of this trait
+/// (with `From<u8>` and `BitXor` for delimiters other than zero, see
+/// [`Cobs`](crate::Cobs)), so the functions here share their implementation.
+pub trait Symbol: sealed::Sealed + Copy + Eq + Debug + From<u8> + BitXor<Output = Self> {
+    /// The delimiter, zero.
+    const ZERO: Self;
+
+    /// Longest run of non-zero symbols that can be encoded with one length
+    /// symbol.
+    const MAX_RUN: usize;
+
+    /// Encodes a run length (between `0` and `MAX_RUN` inclusive) as a
+    /// non-zero symbol.
+    fn encode_len(len: usize) -> Self;
+
+    /// Decodes a length symbol. Returns `None` if the symbol is zero.
+    fn decode_len(self) -> Option<usize>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+}
+
+impl Symbol for u8 {
+    const ZERO: Self = 0;
+    const MAX_RUN: usize = crate::MAX_RUN;
+
+    #[inline(always)]
+    fn encode_len(len: usize) -> Self {
+        crate::encode_len(len)
+    }
+
+    #[inline(always)]
+    fn decode_len(self) -> Option<usize> {
+        crate::decode_len(self)
+    }
+}
+
+impl Symbol for u16 {
+    const ZERO: Self = 0;
+    const MAX_RUN: usize = u16::MAX as usize - 1;
+
+    #[inline(always)]
+    fn encode_len(len: usize) -> Self {
+        debug_assert!(len <= Self::MAX_RUN);
+        len as u16 + 1
+    }
+
+    #[inline(always)]
+    fn decode_len(self) -> Option<usize> {
+        usize::from(self).checked_sub(1)
+    }
+}
+
+/// Returns the largest possible encoded size, in symbols, for an input
+/// message of `raw_len` symbols of type `S`.
+///
+/// This is a `const fn`, like the crate's
+/// [`max_encoded_len`](crate::max_encoded_len):
+///
+/// ```
+/// let msg = [0xFFFF_u16; 70000];
+/// let mut encoded = vec![0; corncobs::symbol::max_encoded_len::<u16>(70000)];
+/// let len = corncobs::symbol::encode_buf(&msg, &mut encoded);
+/// assert_eq!(len, encoded.len());
+/// assert_eq!(len, 70000 + 2 + 1);
+/// ```
+pub const fn max_encoded_len<S: Symbol>(raw_len: usize) -> usize {
+    let overhead = if raw_len == 0 {
+        1
+    } else {
+        (raw_len + S::MAX_RUN - 1) / S::MAX_RUN
+    };
+    // +1 for terminator.
+    raw_len + overhead + 1
+}
+
+/// Returns the largest possible encoded size, in symbols, like
+/// [`max_encoded_len`], or `None` if that would overflow a `usize`. See the
+/// crate's [`checked_max_encoded_len`](crate::checked_max_encoded_len).
+///
+/// ```
+/// use corncobs::symbol;
+///
+/// assert_eq!(symbol::checked_max_encoded_len::<u16>(65534), Some(65536));
+/// assert_eq!(symbol::checked_max_encoded_len::<u16>(usize::MAX), None);
+/// ```
+pub const fn checked_max_encoded_len<S: Symbol>(raw_len: usize) -> Option<usize> {
+    let overhead = if raw_len == 0 {
+        1
+    } else {
+        // Like `max_encoded_len`, but without overflowing on the way.
+        (raw_len - 1) / S::MAX_RUN + 1
+    };
+    match raw_len.checked_add(overhead) {
+        Some(n) => n.checked_add(1),
+        None => None,
+    }
+}
+
+/// Returns the smallest possible encoded size, in symbols, for an input
+/// message of `raw_len` symbols of type `S`.
+///
+/// Every message takes at least one length symbol and a terminator, whatever
+/// the symbol type, so this is `raw_len + 2`, as for the crate's
+/// [`min_encoded_len`](crate::min_encoded_len).
+pub const fn min_encoded_len<S: Symbol>(raw_len: usize) -> usize {
+    raw_len + 2
+}
+
+/// Returns the smallest possible encoded size, in symbols, like
+/// [`min_encoded_len`], or `None` if that would overflow a `usize`.
+pub const fn checked_min_encoded_len<S: Symbol>(raw_len: usize) -> Option<usize> {
+    raw_len.checked_add(2)
+}
+
+/// Returns the largest number of symbols that an encoded message of
+/// `encoded_len` symbols (including the terminator) can decode to, like the
+/// crate's [`max_decoded_len`](crate::max_decoded_len).
+///
+/// This can't overflow, so there's no need for a checked version.
+pub const fn max_decoded_len<S: Symbol>(encoded_len: usize) -> usize {
+    encoded_len.saturating_sub(2)
+}
+
+/// Returns the exact number of symbols that [`encode_buf`] will produce for
+/// `symbols`, including the terminator.
+pub fn encoded_len<S: Symbol>(symbols: &[S]) -> usize {
+    crate::encoded_len_core(symbols)
+}
+
+/// Encodes the message `symbols` into the buffer `output`. Returns the number
+/// of symbols used in `output`, which also happens to be the index of the
+/// first zero.
+///
+/// Symbols in `output` after the part that gets used are left unchanged.
+///
+/// # Panics
+///
+/// If `output` is too small to contain the encoded form of `input`, which is
+/// `max_encoded_len::<S>(symbols.len())` symbols worst-case.
+pub fn encode_buf<S: Symbol>(symbols: &[S], output: &mut [S]) -> usize {
+    crate::encode_buf_core::<S, ZERO>(symbols, output)
+}
+
+/// Encodes `symbols` into the vector `output`. This is a convenience for cases
+/// where you have `std` available.
+#[cfg(feature = "std")]
+pub fn encode<S: Symbol>(symbols: &[S], output: &mut Vec<S>) {
+    let offset = output.len();
+    output.resize(offset + max_encoded_len::<S>(symbols.len()), S::ZERO);
+    let actual_len = encode_buf(symbols, &mut output[offset..]);
+    output.truncate(offset + actual_len);
+}
+
+/// Decodes input from `symbols` into `output` starting at index 0. Returns the
+/// number of symbols used in `output`.
+///
+/// This works like the crate's [`decode_buf`](crate::decode_buf): `output` is
+/// large enough if it's at least `symbols.len()` long, and if it's too small,
+/// you'll get `CobsError::OutputTooSmall` with the size it needs. Like
+/// `decode_buf`, this doesn't check for zeroes in the middle of a message.
+pub fn decode_buf<S: Symbol>(symbols: &[S], output: &mut [S]) -> Result<usize, CobsError> {
+    decode_buf_with_consumed(symbols, output).map(|d| d.decoded_len)
+}
+
+/// Decodes input from `symbols` into `output` starting at index 0, and also
+/// reports how many symbols made up the message, including the terminator,
+/// like [`decode_buf_with_consumed`](crate::decode_buf_with_consumed).
+pub fn decode_buf_with_consumed<S: Symbol>(
+    symbols: &[S],
+    output: &mut [S],
+) -> Result<Decoded, CobsError> {
+    crate::decode_buf_core::<S, false, false, ZERO>(symbols, output).map_err(|e| e.kind)
+}
+
+/// Decodes `symbols` into a vector. This is a convenience for cases where you
+/// have `std` available.
+#[cfg(feature = "std")]
+pub fn decode<S: Symbol>(symbols: &[S], output: &mut Vec<S>) -> Result<(), CobsError> {
+    let offset = output.len();
+    output.resize(offset + symbols.len(), S::ZERO);
+    let actual_len = decode_buf(symbols, &mut output[offset..])?;
+    output.truncate(offset + actual_len);
+    Ok(())
+}
+
+/// Decodes an encoded message in-place, like
+/// [`decode_in_place`](crate::decode_in_place). Returns the number of decoded
+/// symbols, which are deposited at the start of `symbols`.
+///
+/// As with `decode_in_place`, the terminator is optional.
+pub fn decode_in_place<S: Symbol>(symbols: &mut [S]) -> Result<usize, CobsError> {
+    crate::decode_in_place_inner::<S, false, false, ZERO>(symbols)
+        .map(|(len, _)| len)
+        .map_err(|e| e.kind)
+}
+
+/// Incremental decoder for any symbol type, for processing a stream of
+/// symbols as they arrive. This is the crate's [`Decoder`](crate::Decoder),
+/// with lengths (for `with_max_len` and `skipped`) counted in symbols.
+pub type Decoder<S> = crate::Decoder<ZERO, S>;
+
+/// Creates an incremental [`Decoder`] for symbols of type `S`, like
+/// `Decoder::new` does for bytes.
+///
+/// ```
+/// let mut decoder = corncobs::symbol::decoder::<u16>();
+/// let mut output = [0; 4];
+///
+/// let r = decoder.feed(&[0x0003, 0x1111], &mut output).unwrap();
+/// assert_eq!((r.consumed, r.produced, r.frame_done), (2, 1, false));
+///
+/// let r = decoder.feed(&[0x2222, 0x0000], &mut output[1..]).unwrap();
+/// assert_eq!((r.consumed, r.produced, r.frame_done), (2, 1, true));
+/// assert_eq!(&output[..2], [0x1111, 0x2222]);
+/// ```
+pub fn decoder<S: Symbol>() -> Decoder<S> {
+    crate::Decoder::empty()
+}
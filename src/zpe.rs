@@ -0,0 +1,477 @@
+//! COBS/ZPE, or COBS with zero-pair elimination: a variant that encodes pairs
+//! of zeroes more compactly.
+//!
+//! Standard COBS handles a lone zero in a message for free, by turning it into
+//! a length byte, but a long stretch of zeroes costs one byte each (as a chain
+//! of `0x01` length bytes). COBS/ZPE, from Cheshire and Baker's original COBS
+//! paper, divides up the length byte's values differently:
+//!
+//! | Length byte   | Followed by           | Decodes to                       |
+//! |---------------|-----------------------|----------------------------------|
+//! | `0x01`-`0xDF` | `n - 1` bytes         | those bytes, then a zero         |
+//! | `0xE0`        | 223 bytes             | those bytes                      |
+//! | `0xE1`-`0xFF` | `n - 0xE1` bytes      | those bytes, then two zeroes     |
+//!
+//! So a pair of zeroes, along with up to 30 bytes in front of it, costs one
+//! byte _less_ than it would unencoded, and a run of zeroes shrinks to half its
+//! length. The price is that long runs without zeroes are split every 223
+//! bytes rather than every 254, so the worst case is slightly worse; use this
+//! module's [`max_encoded_len`] rather than the crate's to size buffers. As
+//! elsewhere in this crate, messages are terminated by a zero.
+//!
+//! ```
+//! use corncobs::zpe;
+//!
+//! let msg = [0x11, 0x00, 0x00, 0x22, 0x00, 0x00, 0x00, 0x00];
+//! let mut encoded = [0; zpe::max_encoded_len(8)];
+//! let n = zpe::encode_buf(&msg, &mut encoded);
+//! // Standard COBS would produce 02 11 01 02 22 01 01 01 01 00.
+//! assert_eq!(&encoded[..n], [0xE2, 0x11, 0xE2, 0x22, 0xE1, 0x01, 0x00]);
+//!
+//! let mut decoded = [0; 8];
+//! assert_eq!(zpe::decode_buf(&encoded[..n], &mut decoded), Ok(8));
+//! assert_eq!(decoded, msg);
+//! ```
+//!
+//! Since messages can grow when they're decoded, there's no in-place decoder;
+//! use [`decode_buf`], or [`Decoder`] for streams, with an output buffer sized
+//! using [`max_decoded_len`].
+//!
+//! Both decoders reject a zero in the middle of a run as `CobsError::Corrupt`,
+//! like the crate's [`Decoder`](crate::Decoder). The [`Decoder`] is the
+//! reference for what counts as a valid frame: given the whole frame,
+//! `decode_buf` accepts exactly what it does, and produces the same output.
+
+use crate::{copy_run, CobsError, DecodeError, Decoded, FeedError, FeedResult, ZERO};
+
+/// Longest run of bytes that can be encoded without a zero after it, using
+/// the length byte `0xE0`.
+const MAX_RUN: usize = 223;
+
+/// Longest run of bytes that can be encoded before a single zero.
+const MAX_ZERO_RUN: usize = 222;
+
+/// Longest run of bytes that can be encoded before a pair of zeroes.
+const MAX_PAIR_RUN: usize = 30;
+
+/// Length byte for a maximal run without a zero after it.
+const MAX_RUN_CODE: u8 = 0xE0;
+
+/// Returns the largest possible COBS/ZPE encoded size for an input message of
+/// `raw_len` bytes, considering overhead.
+///
+/// This is the same as the crate's [`max_encoded_len`](crate::max_encoded_len),
+/// except that messages without zeroes need an extra byte every 223 bytes,
+/// rather than every 254.
+///
+/// ```
+/// let msg = [0xFF; 300];
+/// let mut encoded = [0; corncobs::zpe::max_encoded_len(300)];
+/// let len = corncobs::zpe::encode_buf(&msg, &mut encoded);
+/// assert_eq!(len, encoded.len());
+/// ```
+pub const fn max_encoded_len(raw_len: usize) -> usize {
+    let overhead = if raw_len == 0 {
+        1
+    } else {
+        (raw_len + MAX_RUN - 1) / MAX_RUN
+    };
+    // +1 for terminator byte.
+    raw_len + overhead + 1
+}
+
+/// Returns the largest size that a COBS/ZPE encoded message of `encoded_len`
+/// bytes (including the terminator) can decode to.
+///
+/// Unlike standard COBS, decoding can make a message longer: at worst, nearly
+/// twice as long, if it's all zeroes.
+pub const fn max_decoded_len(encoded_len: usize) -> usize {
+    // Every length byte can stand for a pair of zeroes, except that the last
+    // zero of the message isn't really there.
+    encoded_len.saturating_mul(2).saturating_sub(3)
+}
+
+/// Encodes the message `bytes` into the buffer `output` using COBS/ZPE.
+/// Returns the number of bytes used in `output`, which also happens to be the
+/// index of the first zero byte.
+///
+/// Bytes in `output` after the part that gets used are left unchanged.
+///
+/// # Panics
+///
+/// If `output` is too small to contain the encoded form of `input`, which is
+/// this module's `max_encoded_len(bytes.len())` bytes worst-case.
+pub fn encode_buf(bytes: &[u8], output: &mut [u8]) -> usize {
+    debug_assert!(output.len() >= max_encoded_len(bytes.len()));
+
+    // As in standard COBS, we encode the message as though it had an extra
+    // zero on the end, which the decoder drops. `inpos` passes the end of
+    // `bytes` once we've used that zero.
+    let mut inpos = 0;
+    let mut outpos = 0;
+    while inpos <= bytes.len() {
+        let rest = &bytes[inpos..];
+        let max_len = usize::min(rest.len(), MAX_RUN);
+        let run_len = rest[..max_len]
+            .iter()
+            .position(|&b| b == ZERO)
+            .unwrap_or(max_len);
+        output[outpos + 1..outpos + 1 + run_len].copy_from_slice(&rest[..run_len]);
+
+        if run_len == MAX_RUN {
+            output[outpos] = MAX_RUN_CODE;
+            outpos += 1 + run_len;
+            inpos += run_len;
+            // Like `take_run`, don't follow a maximal run at the very end
+            // with an empty one.
+            if inpos == bytes.len() {
+                break;
+            }
+            continue;
+        }
+
+        // The run is followed by a zero, either real or imaginary. If there's
+        // another one after it, and the run is short enough, take both.
+        let pair = run_len <= MAX_PAIR_RUN
+            && match rest.get(run_len + 1) {
+                Some(&b) => b == ZERO,
+                None => run_len + 1 == rest.len(),
+            };
+        if pair {
+            output[outpos] = 0xE1 + run_len as u8;
+            inpos += run_len + 2;
+        } else {
+            debug_assert!(run_len <= MAX_ZERO_RUN);
+            output[outpos] = run_len as u8 + 1;
+            inpos += run_len + 1;
+        }
+        outpos += 1 + run_len;
+    }
+    output[outpos] = ZERO;
+    outpos + 1
+}
+
+/// Encodes `bytes` into the vector `output` using COBS/ZPE. This is a
+/// convenience for cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn encode(bytes: &[u8], output: &mut Vec<u8>) {
+    let offset = output.len();
+    output.resize(offset + max_encoded_len(bytes.len()), 0);
+    let actual_len = encode_buf(bytes, &mut output[offset..]);
+    output.truncate(offset + actual_len);
+}
+
+/// Decodes a COBS/ZPE length byte (which must not be zero) into the length of
+/// the run after it, and the number of zeroes that follow the run.
+fn decode_code(code: u8) -> (usize, usize) {
+    match code {
+        MAX_RUN_CODE => (MAX_RUN, 0),
+        0xE1..=0xFF => (usize::from(code - 0xE1), 2),
+        _ => (usize::from(code) - 1, 1),
+    }
+}
+
+/// Decodes COBS/ZPE input from `bytes` into `output` starting at index 0.
+/// Returns the number of bytes used in `output`.
+///
+/// The decoded form can be longer than the encoded form, so make sure `output`
+/// is at least [`max_decoded_len`]`(bytes.len())` bytes long, or be ready to
+/// handle `CobsError::OutputTooSmall`, which gives the number of bytes the
+/// decoded message needs.
+///
+/// Unlike the crate's [`decode_buf`](crate::decode_buf), this checks for zeroes
+/// in the middle of a message, and reports them as `CobsError::Corrupt`, so
+/// that it agrees with the [`Decoder`].
+pub fn decode_buf(bytes: &[u8], output: &mut [u8]) -> Result<usize, CobsError> {
+    decode_buf_with_consumed(bytes, output).map(|d| d.decoded_len)
+}
+
+/// Decodes COBS/ZPE input from `bytes` into `output` starting at index 0, and
+/// also reports how many bytes of `bytes` made up the message, including the
+/// terminator, like
+/// [`decode_buf_with_consumed`](crate::decode_buf_with_consumed).
+pub fn decode_buf_with_consumed(bytes: &[u8], output: &mut [u8]) -> Result<Decoded, CobsError> {
+    let mut inpos = 0;
+    let mut outpos = 0;
+    // Zeroes owed from the last run, which we write once we know they aren't
+    // the imaginary one at the end.
+    let mut zeros: usize = 0;
+    // Once we run out of output, we keep going without writing anything, to
+    // find out how much would have been enough.
+    let mut overflow = false;
+    loop {
+        let code = *bytes.get(inpos).ok_or(CobsError::Truncated)?;
+        if code == ZERO {
+            // Drop the imaginary zero, if the last run ended in one.
+            let end = outpos + zeros.saturating_sub(1);
+            if overflow || output.len() < end {
+                return Err(CobsError::OutputTooSmall { needed: end });
+            }
+            output[outpos..end].fill(ZERO);
+            return Ok(Decoded {
+                decoded_len: end,
+                consumed: inpos + 1,
+            });
+        }
+        let (n, new_zeros) = decode_code(code);
+        let end = usize::min(inpos + 1 + n, bytes.len());
+        if bytes[inpos + 1..end].contains(&ZERO) {
+            return Err(CobsError::Corrupt);
+        }
+        let block = bytes
+            .get(inpos + 1..inpos + 1 + n)
+            .ok_or(CobsError::Truncated)?;
+        let start = outpos + zeros;
+        overflow |= output.len() < start + n;
+        if !overflow {
+            output[outpos..start].fill(ZERO);
+            output[start..start + n].copy_from_slice(block);
+        }
+        inpos += 1 + n;
+        outpos = start + n;
+        zeros = new_zeros;
+    }
+}
+
+/// Decodes COBS/ZPE input from `bytes` into a vector. This is a convenience
+/// for cases where you have `std` available.
+#[cfg(feature = "std")]
+pub fn decode(bytes: &[u8], output: &mut Vec<u8>) -> Result<(), CobsError> {
+    let offset = output.len();
+    output.resize(offset + max_decoded_len(bytes.len()), 0);
+    let actual_len = decode_buf(bytes, &mut output[offset..])?;
+    output.truncate(offset + actual_len);
+    Ok(())
+}
+
+/// Incremental COBS/ZPE decoder, for processing a stream of bytes as they
+/// arrive.
+///
+/// This is a cut-down version of the crate's [`Decoder`](crate::Decoder),
+/// which only has [`feed`](Decoder::feed). (There's no byte-at-a-time
+/// `advance`, since a single length byte can produce two zeroes.) Like that
+/// decoder, it handles a single frame by default, and can be set up to handle
+/// a continuous stream with [`Decoder::with_resync`].
+///
+/// ```
+/// let mut decoder = corncobs::zpe::Decoder::new();
+/// let mut output = [0; 8];
+///
+/// let r = decoder.feed(&[0xE2, 0x11], &mut output).unwrap();
+/// assert_eq!((r.consumed, r.produced, r.frame_done), (2, 1, false));
+///
+/// let r = decoder.feed(&[0x02, 0x22, 0x00], &mut output[1..]).unwrap();
+/// assert_eq!((r.consumed, r.produced, r.frame_done), (3, 3, true));
+/// assert_eq!(&output[..4], [0x11, 0x00, 0x00, 0x22]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Decoder {
+    state: ZState,
+    /// Whether we recover automatically at the end of a frame, or on error.
+    resync: bool,
+    /// Number of bytes dropped while hunting for a frame boundary.
+    skipped: usize,
+    /// Number of bytes of the current frame consumed so far.
+    offset: usize,
+    /// Length byte of the current run, if we're in one.
+    code: Option<u8>,
+    /// Decoded length of the current frame so far.
+    len: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ZState {
+    /// Expecting a length byte or the terminator, and owing this many zeroes
+    /// from the last run.
+    Tween(usize),
+    /// Copying a run with this many bytes left, to be followed by this many
+    /// zeroes.
+    Literal(usize, usize),
+    /// Dropping bytes until we see a zero.
+    Hunting,
+    Done,
+}
+
+impl Default for ZState {
+    fn default() -> Self {
+        Self::Tween(0)
+    }
+}
+
+impl Decoder {
+    /// Creates a `Decoder` that will process a single frame. This is the same
+    /// as `Decoder::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables automatic resynchronization, as for the crate's
+    /// [`Decoder::with_resync`](crate::Decoder::with_resync): after the end of
+    /// a frame, the next byte starts a new one, and after an error, the
+    /// decoder drops input up to the next zero.
+    ///
+    /// ```
+    /// let mut decoder = corncobs::zpe::Decoder::new().with_resync(true);
+    /// let mut output = [0; 8];
+    /// let mut input: &[u8] = &[0xE3, 0x11, 0x00, 0x02, 0x22, 0x00, 0xE1, 0x00];
+    ///
+    /// // The first frame is cut short...
+    /// let e = decoder.feed(input, &mut output).unwrap_err();
+    /// assert_eq!(e.error, corncobs::CobsError::Corrupt);
+    /// input = &input[e.consumed..];
+    ///
+    /// // ...but the decoder picks up with the next, and the one after.
+    /// let r = decoder.feed(input, &mut output).unwrap();
+    /// assert_eq!(&output[..r.produced], [0x22]);
+    /// input = &input[r.consumed..];
+    /// let r = decoder.feed(input, &mut output).unwrap();
+    /// assert_eq!(&output[..r.produced], [0x00]);
+    /// ```
+    pub fn with_resync(mut self, enabled: bool) -> Self {
+        self.resync = enabled;
+        self
+    }
+
+    /// Discards any partially decoded frame, and prepares to decode a new
+    /// one. The byte counter used by [`Decoder::skipped`] is not affected.
+    pub fn reset(&mut self) {
+        self.state = ZState::default();
+        self.start_frame();
+    }
+
+    /// Discards any partially decoded frame, and drops input until the next
+    /// frame boundary (zero byte), as for the crate's
+    /// [`Decoder::hunt`](crate::Decoder::hunt).
+    pub fn hunt(&mut self) {
+        self.state = ZState::Hunting;
+        self.start_frame();
+    }
+
+    /// Checks whether the decoder is dropping input, looking for a frame
+    /// boundary.
+    pub fn is_hunting(&self) -> bool {
+        self.state == ZState::Hunting
+    }
+
+    /// Returns the total number of bytes dropped while hunting for frame
+    /// boundaries. This doesn't include the zero bytes that end each hunt.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Checks whether the decoder has reached the end of the frame.
+    pub fn is_done(&self) -> bool {
+        self.state == ZState::Done
+    }
+
+    /// Forgets the progress of the current frame.
+    fn start_frame(&mut self) {
+        self.offset = 0;
+        self.code = None;
+        self.len = 0;
+    }
+
+    /// Decodes a chunk of input into `output`, continuing from wherever the
+    /// last call left off. This behaves like the crate's
+    /// [`Decoder::feed`](crate::Decoder::feed).
+    ///
+    /// # Panics
+    ///
+    /// If called after the end of the frame has been reached, unless
+    /// resynchronization is enabled.
+    pub fn feed(&mut self, input: &[u8], output: &mut [u8]) -> Result<FeedResult, FeedError> {
+        let mut inpos = 0;
+        let mut outpos = 0;
+        while inpos < input.len() {
+            match self.state {
+                ZState::Literal(left, zeros) => {
+                    if outpos == output.len() {
+                        // Output is full.
+                        break;
+                    }
+                    let k = copy_run(&input[inpos..], &mut output[outpos..], left, ZERO);
+                    if k == 0 {
+                        // There's a zero in the run. With resync, it's the
+                        // boundary before the next frame. Without, we stay
+                        // put, as the crate's `Decoder` does; the frame
+                        // didn't end, so we're not done.
+                        if self.resync {
+                            self.state = ZState::default();
+                        }
+                        let e = DecodeError {
+                            kind: CobsError::Corrupt,
+                            offset: self.offset,
+                            code: self.code,
+                            output_len: self.len,
+                        };
+                        self.start_frame();
+                        return Err(FeedError::new(e, inpos + 1, outpos));
+                    }
+                    inpos += k;
+                    outpos += k;
+                    self.offset += k;
+                    self.len += k;
+                    self.state = if left == k {
+                        ZState::Tween(zeros)
+                    } else {
+                        ZState::Literal(left - k, zeros)
+                    };
+                }
+                ZState::Tween(zeros) => {
+                    let code = input[inpos];
+                    // At the end of the frame, the last zero is imaginary.
+                    let owed = if code == ZERO {
+                        zeros.saturating_sub(1)
+                    } else {
+                        zeros
+                    };
+                    // Pay off as many zeroes as will fit, in case the output
+                    // is too short for all of them.
+                    let k = owed.min(output.len() - outpos);
+                    output[outpos..outpos + k].fill(ZERO);
+                    outpos += k;
+                    self.len += k;
+                    if k < owed {
+                        self.state = ZState::Tween(zeros - k);
+                        break;
+                    }
+                    inpos += 1;
+                    self.offset += 1;
+                    if code == ZERO {
+                        self.state = ZState::Done;
+                        self.start_frame();
+                        return Ok(FeedResult {
+                            consumed: inpos,
+                            produced: outpos,
+                            frame_done: true,
+                        });
+                    }
+                    self.code = Some(code);
+                    self.state = match decode_code(code) {
+                        (0, zeros) => ZState::Tween(zeros),
+                        (n, zeros) => ZState::Literal(n, zeros),
+                    };
+                }
+                ZState::Hunting => {
+                    // Skip everything up to the next zero in one go.
+                    let rest = &input[inpos..];
+                    if let Some(i) = rest.iter().position(|&b| b == ZERO) {
+                        self.skipped = self.skipped.saturating_add(i);
+                        self.state = ZState::default();
+                        inpos += i + 1;
+                    } else {
+                        self.skipped = self.skipped.saturating_add(rest.len());
+                        inpos = input.len();
+                    }
+                }
+                ZState::Done if self.resync => self.state = ZState::default(),
+                ZState::Done => panic!("feed called after end of frame"),
+            }
+        }
+        Ok(FeedResult {
+            consumed: inpos,
+            produced: outpos,
+            frame_done: false,
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! Tests for the `std::io` adapters, which only exist with the `std` feature.
+//! Run these with `cargo test --features std`.
+
+#![cfg(feature = "std")]
+
+use std::io::{BufRead, Read, Write};
+
+use corncobs::*;
+
+#[test]
+fn writer_matches_encode_buf() {
+    let mut input = vec![];
+    for i in 0..2000 {
+        input.push((i % 300) as u8);
+    }
+    let mut expected = vec![0; max_encoded_len(input.len())];
+    let n = encode_buf(&input, &mut expected);
+    expected.truncate(n);
+
+    for chunk in [1, 7, 254, 255, 4096] {
+        let mut w = CobsWriter::new(vec![]);
+        for piece in input.chunks(chunk) {
+            w.write_all(piece).unwrap();
+        }
+        assert_eq!(w.finish().unwrap(), expected, "chunk {}", chunk);
+    }
+}
+
+#[test]
+fn writer_multiple_frames() {
+    let mut w = CobsWriter::new(vec![]);
+    w.write_all(&[0x11]).unwrap();
+    w.end_frame().unwrap();
+    w.write_all(&[0x00]).unwrap();
+    w.end_frame().unwrap();
+    assert_eq!(w.get_ref(), &[0x02, 0x11, 0x00, 0x01, 0x01, 0x00]);
+}
+
+#[test]
+fn writer_reports_errors() {
+    // A slice writer fails once it's full.
+    let mut storage = [0; 4];
+    let mut w = CobsWriter::new(&mut storage[..]);
+    // The first run only goes out once it's complete, after it's been
+    // accepted, so the error turns up on the next call.
+    assert_eq!(w.write(&[0x11, 0x22, 0x33, 0x44, 0x00]).unwrap(), 5);
+    w.write(&[0x55]).unwrap_err();
+    // The error is only reported once, and nothing more was accepted.
+    assert_eq!(w.write(&[0x66]).unwrap(), 1);
+    w.end_frame().unwrap_err();
+}
+
+/// A `BufRead` that hands out its data a few bytes at a time, to simulate
+/// frames split across reads.
+struct Trickle<'a> {
+    data: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = usize::min(buf.len(), self.fill_buf()?.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for Trickle<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        Ok(&self.data[..usize::min(self.step, self.data.len())])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.data = &self.data[amt..];
+    }
+}
+
+#[test]
+fn reader_split_frames() {
+    let messages: &[&[u8]] = &[&[], &[0x00], &[0x11, 0x22, 0x00, 0x33], &[0xAA; 600]];
+    let mut stream = vec![];
+    for m in messages {
+        encode(m, &mut stream);
+    }
+
+    for step in [1, 2, 3, 255, 10000] {
+        let r = CobsReader::new(Trickle { data: &stream, step });
+        let frames: Vec<Vec<u8>> = r.frames().collect::<Result<_, _>>().unwrap();
+        assert_eq!(frames, messages, "step {}", step);
+    }
+}
+
+#[test]
+fn reader_resyncs_after_errors() {
+    let stream: &[u8] = &[
+        0x05, 0x11, 0x00, // truncated run
+        0x02, 0x22, 0x00, // good frame
+        0x03, 0x33, // ends mid-frame
+    ];
+    let mut r = CobsReader::new(stream).frames();
+
+    let e = r.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(r.next().unwrap().unwrap(), [0x22]);
+    let e = r.next().unwrap().unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+    let e = e.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+    assert_eq!(
+        *e,
+        DecodeError { kind: CobsError::Truncated, offset: 2, code: Some(0x03), output_len: 1 },
+    );
+    assert!(r.next().is_none());
+}
+
+#[test]
+fn reader_with_options() {
+    let stream: &[u8] = &[
+        0x03, 0x11, 0x00, // run runs into the terminator
+        0x00, // empty, but not canonical
+        0x02, 0x33, 0x00, // good
+    ];
+    let mut r = CobsReader::new(stream)
+        .with_options(DecodeOptions::new().canonical(true))
+        .frames();
+
+    for (kind, offset) in [(CobsError::Corrupt, 2), (CobsError::NonCanonical, 0)] {
+        let e = r.next().unwrap().unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        let e = e.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+        assert_eq!((e.kind, e.offset), (kind, offset));
+    }
+    assert_eq!(r.next().unwrap().unwrap(), [0x33]);
+    assert!(r.next().is_none());
+}
+
+#[test]
+fn reader_max_len() {
+    let mut stream = vec![];
+    encode(&[0x11; 310], &mut stream);
+    // A stuck line, long enough to need two hunts.
+    stream.extend_from_slice(&[0xEE; 700]);
+    stream.push(0x00);
+    // Fits in max_encoded_len(300), but decodes to too many zeroes.
+    encode(&[0x00; 301], &mut stream);
+    encode(&[0x22; 300], &mut stream);
+
+    for step in [1, 4, 10000] {
+        let mut r = CobsReader::new(Trickle { data: &stream, step })
+            .with_max_len(300)
+            .frames();
+        let mut errors = vec![];
+        for _ in 0..4 {
+            let e = r.next().unwrap().unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+            let e = e.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+            assert_eq!(e.kind, CobsError::TooLong, "step {}", step);
+            errors.push(*e);
+        }
+        // Where the first frame passed 300 bytes, in its second run.
+        assert_eq!(
+            errors[0],
+            DecodeError { kind: CobsError::TooLong, offset: 302, code: Some(57), output_len: 300 },
+        );
+        // The zeroes, which only turned out to be too long once decoded.
+        assert_eq!(
+            errors[3],
+            DecodeError { kind: CobsError::TooLong, offset: 301, code: Some(1), output_len: 300 },
+        );
+        assert_eq!(r.next().unwrap().unwrap(), [0x22; 300], "step {}", step);
+        assert!(r.next().is_none());
+    }
+}
+
+#[test]
+fn reader_error_payload() {
+    // The payload is a `DecodeError`, so that's what to downcast to.
+    let stream: &[u8] = &[0x04, 0x11, 0x00];
+    let e = CobsReader::new(stream).read_frame(&mut vec![]).unwrap_err();
+    let inner = e.into_inner().unwrap();
+    assert!(inner.downcast_ref::<CobsError>().is_none());
+    let e = inner.downcast_ref::<DecodeError>().unwrap();
+    assert_eq!(e.kind, CobsError::Truncated);
+}
+
+#[test]
+fn reader_max_len_unbounded() {
+    // The largest limit there is, which mustn't overflow working out sizes.
+    let mut stream = vec![];
+    encode(&[0x11], &mut stream);
+    encode(&[0x22; 600], &mut stream);
+    let frames: Vec<Vec<u8>> = CobsReader::new(&stream[..])
+        .with_max_len(usize::MAX)
+        .frames()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(frames, [vec![0x11], vec![0x22; 600]]);
+}
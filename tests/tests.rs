@@ -313,3 +313,1773 @@ fn fixture_round_trip_in_place() {
         assert_eq!(&encoded[..n], *input, "mismatch in case {}", i);
     }
 }
+
+fn encode_with_encoder(input: &[u8], chunk: usize) -> Vec<u8> {
+    let mut out = vec![];
+    let mut enc = Encoder::new(|b: &[u8]| out.extend_from_slice(b));
+    if chunk == 0 {
+        for &b in input {
+            enc.push_byte(b);
+        }
+    } else {
+        for piece in input.chunks(chunk) {
+            enc.push(piece);
+        }
+    }
+    enc.finish();
+    out
+}
+
+#[test]
+fn encoder_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        for chunk in [0, 1, 2, 3, 253, 254, 255, 1000] {
+            let actual = encode_with_encoder(input, chunk);
+            assert_eq!(actual, expected, "mismatch in case {} chunk {}", i, chunk);
+        }
+    }
+}
+
+#[test]
+fn encoder_max_run_then_zero() {
+    let mut input = vec![0xAA; 254];
+    input.push(0);
+    let mut expected = vec![0; max_encoded_len(input.len())];
+    let n = encode_buf(&input, &mut expected);
+    expected.truncate(n);
+
+    for chunk in [0, 1, 254, 255] {
+        assert_eq!(encode_with_encoder(&input, chunk), expected);
+    }
+}
+
+#[test]
+fn encoder_multiple_frames() {
+    let mut out = vec![];
+    let mut enc = Encoder::new(|b: &[u8]| out.extend_from_slice(b));
+    enc.push(&[0x11, 0x22, 0x00, 0x33]);
+    enc.end_frame();
+    enc.end_frame();
+    enc.finish();
+    assert_eq!(out, [0x03, 0x11, 0x22, 0x02, 0x33, 0x00, 0x01, 0x00, 0x01, 0x00]);
+}
+
+fn all_fixtures() -> Vec<(&'static [u8], &'static [u8])> {
+    let mut fixtures = FIXTURES.to_vec();
+    fixtures.extend_from_slice(&[
+        (&LONG_FIXTURE_1.0[..], &LONG_FIXTURE_1.1[..]),
+        (&LONG_FIXTURE_2.0, &LONG_FIXTURE_2.1),
+        (&LONG_FIXTURE_3.0, &LONG_FIXTURE_3.1),
+        (&LONG_FIXTURE_4.0, &LONG_FIXTURE_4.1),
+    ]);
+    fixtures
+}
+
+/// The encoded forms of `all_fixtures`, back to back, for testing decoders that
+/// walk through a stream of messages.
+fn concatenated_fixture_stream() -> Vec<u8> {
+    all_fixtures().iter().flat_map(|(_, e)| e.iter().copied()).collect()
+}
+
+#[test]
+fn sizing_fixtures() {
+    for (input, encoded) in all_fixtures() {
+        assert_eq!(encoded_len(input), encoded.len(), "{:x?}", input);
+        assert!(encoded.len() <= max_encoded_len(input.len()));
+        assert!(encoded.len() >= min_encoded_len(input.len()));
+        assert!(max_decoded_len(encoded.len()) >= input.len());
+        assert_eq!(checked_max_encoded_len(input.len()), Some(max_encoded_len(input.len())));
+        assert_eq!(checked_min_encoded_len(input.len()), Some(min_encoded_len(input.len())));
+    }
+}
+
+#[test]
+fn sizing_bounds_are_tight() {
+    // All zeroes hits the minimum, no zeroes hits the maximum.
+    for len in [0, 1, 253, 254, 255, 508, 1000] {
+        let zeroes = vec![0; len];
+        assert_eq!(encoded_len(&zeroes), min_encoded_len(len));
+        let ones = vec![1; len];
+        assert_eq!(encoded_len(&ones), max_encoded_len(len));
+        // Something that encodes to `min_encoded_len` decodes back out to the
+        // largest size that `max_decoded_len` allows.
+        assert_eq!(max_decoded_len(min_encoded_len(len)), len);
+    }
+    assert_eq!(max_decoded_len(0), 0);
+    assert_eq!(max_decoded_len(1), 0);
+}
+
+#[test]
+fn checked_sizing_overflow() {
+    assert_eq!(checked_max_encoded_len(usize::MAX), None);
+    assert_eq!(checked_max_encoded_len(usize::MAX - 2), None);
+    assert!(checked_max_encoded_len(usize::MAX / 2).is_some());
+    assert_eq!(checked_min_encoded_len(usize::MAX - 1), None);
+    assert_eq!(checked_min_encoded_len(usize::MAX - 2), Some(usize::MAX));
+
+    const N: Option<usize> = checked_max_encoded_len(1000);
+    assert_eq!(N, Some(max_encoded_len(1000)));
+}
+
+#[test]
+fn vectored_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        // Try splitting the input into three parts at every pair of points,
+        // including empty parts.
+        for a in 0..=input.len() {
+            for b in a..=input.len() {
+                let parts = [&input[..a], &input[a..b], &input[b..]];
+
+                let mut actual = vec![0; max_encoded_len(input.len())];
+                let n = encode_vectored_buf(&parts, &mut actual);
+                assert_eq!(&actual[..n], expected, "buf case {} split {}/{}", i, a, b);
+
+                let actual: Vec<u8> = encode_vectored_iter(&parts).collect();
+                assert_eq!(actual, expected, "iter case {} split {}/{}", i, a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn vectored_no_parts() {
+    let mut actual = [0xDE; 4];
+    let n = encode_vectored_buf(&[], &mut actual);
+    assert_eq!(&actual[..n], [0x01, 0x00]);
+    assert_eq!(encode_vectored_iter(&[]).collect::<Vec<_>>(), [0x01, 0x00]);
+}
+
+#[test]
+fn segments_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        let segments: Vec<&[u8]> = encode_segments(input).collect();
+        assert!(segments.iter().all(|s| !s.is_empty()), "empty segment in case {}", i);
+        assert_eq!(segments.concat(), expected, "mismatch in case {}", i);
+    }
+}
+
+#[test]
+fn segments_borrow_input() {
+    let input = [0x11, 0x22, 0x00, 0x33];
+    let segments: Vec<&[u8]> = encode_segments(&input).collect();
+    assert_eq!(segments.len(), 5);
+    assert!(core::ptr::eq(segments[1], &input[..2]));
+    assert!(core::ptr::eq(segments[3], &input[3..]));
+}
+
+#[test]
+fn encode_in_place_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        // Try both the minimum headroom and some extra.
+        for extra in [0, 1, 300] {
+            let mut buf = vec![0xDE; max_encoded_len(input.len()) + extra];
+            let start = buf.len() - input.len();
+            buf[start..].copy_from_slice(input);
+            let n = encode_in_place(&mut buf, input.len());
+            assert_eq!(&buf[..n], expected, "mismatch in case {} extra {}", i, extra);
+        }
+    }
+}
+
+#[test]
+fn encode_in_place_worst_case() {
+    // Long runs with no zeroes eat into the headroom fastest.
+    for len in [253, 254, 255, 508, 509, 1000] {
+        let input = vec![0xFF; len];
+        let mut expected = vec![0; max_encoded_len(len)];
+        let n = encode_buf(&input, &mut expected);
+        expected.truncate(n);
+
+        let mut buf = vec![0; max_encoded_len(len)];
+        buf[max_encoded_len(len) - len..].copy_from_slice(&input);
+        let n = encode_in_place(&mut buf, len);
+        assert_eq!(&buf[..n], expected, "mismatch at len {}", len);
+    }
+}
+
+#[test]
+#[should_panic]
+fn encode_in_place_short_headroom() {
+    let mut buf = [0xFF; 255];
+    encode_in_place(&mut buf, 254);
+}
+
+#[test]
+fn frame_buf_capacity() {
+    for len in 2..2000 {
+        let mut storage = vec![0; len];
+        let cap = FrameBuf::new(&mut storage).capacity();
+        assert!(max_encoded_len(cap) <= len, "capacity too large for {}", len);
+        assert!(max_encoded_len(cap + 1) > len, "capacity too small for {}", len);
+    }
+}
+
+#[test]
+fn frame_buf_round_trip() {
+    let mut storage = [0; max_encoded_len(1000)];
+    let mut frame = FrameBuf::new(&mut storage);
+    for len in [0, 1, 254, 255, 1000] {
+        for (i, b) in frame.payload_mut()[..len].iter_mut().enumerate() {
+            *b = (i % 251) as u8;
+        }
+        let encoded = frame.encode(len).to_vec();
+        let mut decoded = vec![0; encoded.len()];
+        let n = decode_buf(&encoded, &mut decoded).unwrap();
+        let expected: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        assert_eq!(&decoded[..n], expected, "mismatch at len {}", len);
+    }
+}
+
+#[test]
+fn try_encode_buf_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        for len in 0..=expected.len() + 1 {
+            let mut out = vec![0xDE; len];
+            let result = try_encode_buf(input, &mut out);
+            if len < expected.len() {
+                assert_eq!(
+                    result,
+                    Err(CobsError::OutputTooSmall { needed: expected.len() }),
+                    "case {} len {}", i, len,
+                );
+            } else {
+                let n = result.unwrap();
+                assert_eq!(&out[..n], expected, "case {} len {}", i, len);
+            }
+        }
+    }
+}
+
+#[test]
+fn encode_cursor_fixtures() {
+    for (i, (input, expected)) in all_fixtures().into_iter().enumerate() {
+        for chunk in [1, 2, 3, 64, 255, 256, 1000] {
+            let mut cursor = EncodeCursor::new(input);
+            let mut actual = vec![];
+            let mut buf = vec![0; chunk];
+            while !cursor.is_done() {
+                assert_eq!(cursor.remaining_len(), expected.len() - actual.len());
+                let n = cursor.fill(&mut buf);
+                actual.extend_from_slice(&buf[..n]);
+            }
+            assert_eq!(actual, expected, "case {} chunk {}", i, chunk);
+        }
+    }
+}
+
+#[test]
+fn decode_buf_short_output() {
+    for (i, (expected, encoded)) in all_fixtures().into_iter().enumerate() {
+        for len in 0..expected.len() {
+            let mut out = vec![0xDE; len];
+            assert_eq!(
+                decode_buf(encoded, &mut out),
+                Err(CobsError::OutputTooSmall { needed: expected.len() }),
+                "case {} len {}", i, len,
+            );
+        }
+        let mut out = vec![0xDE; expected.len()];
+        let n = decode_buf(encoded, &mut out).unwrap();
+        assert_eq!(&out[..n], expected, "case {}", i);
+    }
+}
+
+#[test]
+fn decode_buf_short_output_truncated() {
+    // Output is too small, but the input is also missing its end; there's no
+    // right answer for `needed`, so this should be reported as truncation.
+    let mut out = [0; 1];
+    assert_eq!(
+        decode_buf(&[0x03, 0x11, 0x22, 0x04, 0x33], &mut out),
+        Err(CobsError::Truncated),
+    );
+}
+
+#[test]
+fn decode_with_consumed_concatenated() {
+    let mut stream = concatenated_fixture_stream();
+    let mut out = vec![0; stream.len()];
+    let (mut start, mut start_in_place) = (0, 0);
+    for (i, (expected, encoded)) in all_fixtures().into_iter().enumerate() {
+        let d = decode_buf_with_consumed(&stream[start..], &mut out).unwrap();
+        assert_eq!((&out[..d.decoded_len], d.consumed), (expected, encoded.len()), "case {}", i);
+        start += d.consumed;
+    }
+    for (i, (expected, encoded)) in all_fixtures().into_iter().enumerate() {
+        let rest = &mut stream[start_in_place..];
+        let d = decode_in_place_with_consumed(rest).unwrap();
+        assert_eq!((&rest[..d.decoded_len], d.consumed), (expected, encoded.len()), "case {}", i);
+        start_in_place += d.consumed;
+    }
+    assert_eq!((start, start_in_place), (stream.len(), stream.len()));
+}
+
+#[test]
+fn decode_with_consumed_unterminated() {
+    let mut input = [0x03, 0x11, 0x22, 0x02, 0x33];
+    let mut out = [0; 5];
+    assert_eq!(decode_buf_with_consumed(&input, &mut out), Err(CobsError::Truncated));
+    assert_eq!(decode_in_place_with_consumed(&mut input), Err(CobsError::Truncated));
+}
+
+#[test]
+fn frames_fixtures() {
+    let mut stream = concatenated_fixture_stream();
+    let expected: Vec<&[u8]> = all_fixtures().iter().map(|(d, _)| *d).collect();
+    let decoded: Vec<Vec<u8>> = frames(&stream).map(|f| f.unwrap().decoded().collect()).collect();
+    assert_eq!(decoded, expected);
+
+    let mut offset = 0;
+    for (f, (_, encoded)) in frames(&stream).zip(all_fixtures()) {
+        assert_eq!(f.unwrap().range, offset..offset + encoded.len());
+        offset += encoded.len();
+    }
+
+    let in_place: Vec<&[u8]> = frames_in_place(&mut stream).map(|f| &*f.unwrap().decoded).collect();
+    assert_eq!(in_place, expected);
+}
+
+#[test]
+fn frames_skip_bad() {
+    let mut buf = [
+        0x03, 0x11, 0x00, // run runs into the terminator
+        0x02, 0x22, 0x00, // good
+        0x00, // empty
+        0x04, 0x33, // unterminated
+    ];
+    // Offsets in errors are from the start of the buffer.
+    let first = DecodeError {
+        kind: CobsError::Truncated,
+        offset: 3,
+        code: None,
+        output_len: 2,
+    };
+    let last = DecodeError {
+        kind: CobsError::Truncated,
+        offset: 9,
+        code: Some(0x04),
+        output_len: 1,
+    };
+    let mut it = frames(&buf);
+    assert_eq!(it.next(), Some(Err(first)));
+    assert_eq!(it.offset(), 3);
+    let f = it.next().unwrap().unwrap();
+    assert_eq!((f.range.clone(), f.encoded), (3..6, &[0x02, 0x22, 0x00][..]));
+    let mut out = [0; 1];
+    assert_eq!(f.decode_into(&mut out), Ok(1));
+    assert_eq!(it.next().unwrap().unwrap().decoded_len, 0);
+    assert_eq!(it.next(), Some(Err(last)));
+    assert_eq!(it.next(), None);
+
+    let mut it = frames_in_place(&mut buf);
+    assert_eq!(it.next().unwrap().unwrap_err(), first);
+    assert_eq!(it.next().unwrap().unwrap().decoded, [0x22]);
+    assert_eq!(it.next().unwrap().unwrap().decoded, []);
+    assert_eq!(it.next().unwrap().unwrap_err(), last);
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn frames_with_options() {
+    let mut buf = [
+        0x03, 0x11, 0x00, // run runs into the terminator
+        0x00, // empty, but not canonical
+        0x02, 0x33, 0x00, // good
+    ];
+    let strict = DecodeOptions::new().strict(true);
+    let canonical = DecodeOptions::new().canonical(true);
+
+    let mut it = strict.frames(&buf);
+    assert_eq!(it.next().unwrap().unwrap_err().kind, CobsError::Corrupt);
+    assert_eq!(it.next().unwrap().unwrap().decoded_len, 0);
+    assert_eq!(it.next().unwrap().unwrap().decoded_len, 1);
+    assert_eq!(it.next(), None);
+
+    let mut it = canonical.frames(&buf);
+    assert_eq!(it.next().unwrap().unwrap_err().kind, CobsError::Corrupt);
+    let e = it.next().unwrap().unwrap_err();
+    assert_eq!((e.kind, e.offset), (CobsError::NonCanonical, 3));
+    assert_eq!(it.offset(), 4);
+    assert_eq!(it.next().unwrap().unwrap().range, 4..7);
+    assert_eq!(it.next(), None);
+
+    let mut it = canonical.frames_in_place(&mut buf);
+    assert_eq!(it.next().unwrap().unwrap_err().kind, CobsError::Corrupt);
+    let e = it.next().unwrap().unwrap_err();
+    assert_eq!((e.kind, e.offset), (CobsError::NonCanonical, 3));
+    assert_eq!(it.next().unwrap().unwrap().decoded, [0x33]);
+    assert_eq!(it.next(), None);
+}
+
+/// Decodes `input` using each of the strict decode paths, and checks that they
+/// all agree.
+fn check_strict_agreement(input: &[u8]) {
+    type Outcome = Result<(Vec<u8>, usize), CobsError>;
+    let strict = DecodeOptions::new().strict(true);
+
+    let mut out = vec![0; input.len()];
+    let buf: Outcome = strict
+        .decode_buf_with_consumed(input, &mut out)
+        .map(|d| (out[..d.decoded_len].to_vec(), d.consumed));
+
+    let mut scratch = input.to_vec();
+    let in_place: Outcome = strict
+        .decode_in_place_with_consumed(&mut scratch)
+        .map(|d| (scratch[..d.decoded_len].to_vec(), d.consumed));
+    assert_eq!(in_place, buf, "in place, input {:x?}", input);
+
+    // The plain forms, without the consumed length, should agree too.
+    let plain = strict.decode_buf(input, &mut out).map(|n| out[..n].to_vec());
+    assert_eq!(plain, buf.clone().map(|(d, _)| d), "plain, input {:x?}", input);
+    let mut scratch = input.to_vec();
+    let plain_in_place = strict.decode_in_place(&mut scratch).map(|n| scratch[..n].to_vec());
+    assert_eq!(plain_in_place, plain, "plain in place, input {:x?}", input);
+
+    for k in [0, input.len() / 2, input.len()] {
+        let (a, b) = input.split_at(k);
+        let ring: Outcome = strict
+            .decode_ring_buf((a, b), &mut out)
+            .map(|d| (out[..d.decoded_len].to_vec(), d.consumed.0 + d.consumed.1));
+        assert_eq!(ring, buf, "ring split {}, input {:x?}", k, input);
+    }
+
+    let stream: Outcome = match Decoder::new().feed(input, &mut out) {
+        Ok(r) if r.frame_done => Ok((out[..r.produced].to_vec(), r.consumed)),
+        Ok(_) => Err(CobsError::Truncated),
+        Err(e) => {
+            // The decoder never sees the end of the input, but where it does
+            // find a problem, it should describe it the same way.
+            let detail = strict.decode_buf_detailed(input, &mut out).unwrap_err();
+            assert_eq!(DecodeError::from(e), detail, "Decoder, input {:x?}", input);
+            Err(e.error)
+        }
+    };
+    assert_eq!(stream, buf, "Decoder, input {:x?}", input);
+}
+
+#[test]
+fn strict_decoders_agree() {
+    for (expected, encoded) in all_fixtures() {
+        check_strict_agreement(encoded);
+        let mut out = vec![0; encoded.len()];
+        let n = DecodeOptions::new().strict(true).decode_buf(encoded, &mut out).unwrap();
+        assert_eq!(&out[..n], expected);
+
+        // Damage the frame with a zero at each position, and also cut it off
+        // there.
+        for i in 0..encoded.len() - 1 {
+            let mut damaged = encoded.to_vec();
+            damaged[i] = 0;
+            check_strict_agreement(&damaged);
+            check_strict_agreement(&encoded[..i]);
+        }
+    }
+}
+
+#[test]
+fn default_options_match_free_functions() {
+    let options = DecodeOptions::new();
+    let mut out = [0; 8];
+    for input in [&[0x03, 0x11, 0x22, 0x02, 0x33, 0x00][..], &[0x03, 0x11, 0x22, 0x02, 0x33]] {
+        assert_eq!(options.decode_buf(input, &mut out), decode_buf(input, &mut out));
+        assert_eq!(
+            options.decode_in_place(&mut input.to_vec()),
+            decode_in_place(&mut input.to_vec()),
+        );
+    }
+}
+
+#[test]
+fn strict_in_place_needs_terminator() {
+    // Lenient in-place decoding lets the terminator go, but strict decoding
+    // agrees with `decode_buf`.
+    let input = [0x02, 0x11];
+    assert_eq!(decode_in_place(&mut input.clone()), Ok(1));
+    for options in [DecodeOptions::new().strict(true), DecodeOptions::new().canonical(true)] {
+        assert_eq!(options.decode_buf(&input, &mut [0; 2]), Err(CobsError::Truncated));
+        assert_eq!(options.decode_in_place(&mut input.clone()), Err(CobsError::Truncated));
+    }
+}
+
+#[test]
+fn strict_rejects_zero_in_run() {
+    let input = [0x04, 0x11, 0x22, 0x00, 0x02, 0x33, 0x00];
+    let mut out = [0; 8];
+    let strict = DecodeOptions::new().strict(true);
+    assert_eq!(decode_buf(&input, &mut out), Ok(5));
+    assert_eq!(strict.decode_buf(&input, &mut out), Err(CobsError::Corrupt));
+    assert_eq!(strict.decode_in_place(&mut input.clone()), Err(CobsError::Corrupt));
+    assert_eq!(
+        strict.decode_ring_buf((&input[..2], &input[2..]), &mut out),
+        Err(CobsError::Corrupt),
+    );
+    // Even when the output is too small, and even when the input is cut off
+    // before the end of the run.
+    assert_eq!(strict.decode_buf(&input, &mut out[..1]), Err(CobsError::Corrupt));
+    assert_eq!(strict.decode_buf(&[0x05, 0x11, 0x00], &mut out), Err(CobsError::Corrupt));
+}
+
+#[test]
+fn validate_fixtures() {
+    for (expected, encoded) in all_fixtures() {
+        let info = validate(encoded).unwrap();
+        assert_eq!(info.decoded_len, expected.len());
+        assert_eq!(info.consumed, encoded.len());
+        let mut runs = 0;
+        let mut i = 0;
+        while encoded[i] != 0 {
+            runs += 1;
+            i += usize::from(encoded[i]);
+        }
+        assert_eq!(info.runs, runs);
+
+        // On damaged input, we should agree with decode_buf.
+        let mut out = vec![0; encoded.len()];
+        for i in 0..encoded.len() {
+            let mut damaged = encoded.to_vec();
+            damaged[i] ^= 0x55;
+            let from_decode = decode_buf_with_consumed(&damaged, &mut out);
+            let from_validate = validate(&damaged).map(|info| Decoded {
+                decoded_len: info.decoded_len,
+                consumed: info.consumed,
+            });
+            assert_eq!(from_validate, from_decode, "damaged at {}", i);
+
+            let from_decode = decode_buf_with_consumed(&encoded[..i], &mut out);
+            let from_validate = validate(&encoded[..i]).map(|info| Decoded {
+                decoded_len: info.decoded_len,
+                consumed: info.consumed,
+            });
+            assert_eq!(from_validate, from_decode, "truncated at {}", i);
+        }
+    }
+}
+
+#[test]
+fn validate_with_options() {
+    let input = [0x04, 0x11, 0x00, 0x22, 0x00];
+    assert_eq!(
+        validate(&input),
+        Ok(FrameInfo { decoded_len: 3, consumed: 5, runs: 1 }),
+    );
+    let strict = DecodeOptions::new().strict(true);
+    assert_eq!(strict.validate(&input), Err(CobsError::Corrupt));
+    let canonical = DecodeOptions::new().canonical(true);
+    assert_eq!(canonical.validate(&[0x00]), Err(CobsError::NonCanonical));
+    assert_eq!(validate(&[0x00]), Ok(FrameInfo { decoded_len: 0, consumed: 1, runs: 0 }));
+}
+
+#[test]
+fn detailed_errors() {
+    let strict = DecodeOptions::new().strict(true);
+    let canonical = DecodeOptions::new().canonical(true);
+    let mut out = [0; 16];
+
+    let e = DecodeOptions::new()
+        .decode_buf_detailed(&[0x02, 0x11, 0x05, 0x22, 0x33], &mut out)
+        .unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::Truncated,
+            offset: 5,
+            code: Some(0x05),
+            output_len: 4,
+        },
+    );
+
+    let e = DecodeOptions::new()
+        .decode_buf_detailed(&[0x02, 0x11], &mut out)
+        .unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::Truncated,
+            offset: 2,
+            code: None,
+            output_len: 1,
+        },
+    );
+
+    let e = strict
+        .decode_buf_detailed(&[0x03, 0x11, 0x22, 0x04, 0x33, 0x00, 0x00], &mut out)
+        .unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::Corrupt,
+            offset: 5,
+            code: Some(0x04),
+            output_len: 4,
+        },
+    );
+
+    let e = DecodeOptions::new()
+        .decode_buf_detailed(&[0x02, 0x11, 0x04, 0x22, 0x33, 0x44, 0x00], &mut out[..3])
+        .unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::OutputTooSmall { needed: 5 },
+            offset: 2,
+            code: Some(0x04),
+            output_len: 1,
+        },
+    );
+
+    let mut long = vec![0x02, 0x11, 0xFF];
+    long.extend(1..=254);
+    long.extend([0x01, 0x00]);
+    let mut big = vec![0; long.len()];
+    let e = canonical.decode_buf_detailed(&long, &mut big).unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::NonCanonical,
+            offset: 257,
+            code: Some(0x01),
+            output_len: 256,
+        },
+    );
+    let e = canonical.decode_buf_detailed(&[0x00], &mut out).unwrap_err();
+    assert_eq!(
+        e,
+        DecodeError {
+            kind: CobsError::NonCanonical,
+            offset: 0,
+            code: None,
+            output_len: 0,
+        },
+    );
+    assert_eq!(
+        e.to_string(),
+        "input not canonically encoded at offset 0, after 0 bytes of output",
+    );
+}
+
+#[test]
+fn detailed_errors_agree() {
+    for options in [
+        DecodeOptions::new(),
+        DecodeOptions::new().strict(true),
+        DecodeOptions::new().canonical(true),
+    ] {
+        for (_, encoded) in all_fixtures() {
+            let mut out = vec![0; encoded.len()];
+            for i in 0..encoded.len() {
+                for damaged in [
+                    encoded[..i].to_vec(),
+                    {
+                        let mut d = encoded.to_vec();
+                        d[i] = 0;
+                        d
+                    },
+                ] {
+                    let from_decode = options.decode_buf_detailed(&damaged, &mut out);
+                    let from_validate = options.validate_detailed(&damaged);
+                    assert_eq!(
+                        from_decode.map(|d| d.decoded_len),
+                        from_validate.map(|info| info.decoded_len),
+                    );
+                    assert_eq!(
+                        from_decode.map_err(CobsError::from),
+                        options.decode_buf_with_consumed(&damaged, &mut out),
+                    );
+                    if let Err(e) = from_decode {
+                        let in_place = options.decode_in_place_detailed(&mut damaged.clone());
+                        assert_eq!(in_place, Err(e), "in place, input {:x?}", damaged);
+                        for k in [0, damaged.len() / 2, damaged.len()] {
+                            let halves = damaged.split_at(k);
+                            assert_eq!(
+                                options.decode_ring_buf_detailed(halves, &mut out).unwrap_err(),
+                                e,
+                                "ring split {}, input {:x?}",
+                                k,
+                                damaged,
+                            );
+                        }
+                    }
+
+                    // With too little room, the error should be the same as
+                    // for validation, or else OutputTooSmall.
+                    if let Ok(info) = from_validate {
+                        if let Some(short) = info.decoded_len.checked_sub(1) {
+                            let e = options
+                                .decode_buf_detailed(&damaged, &mut out[..short])
+                                .unwrap_err();
+                            assert_eq!(
+                                e.kind,
+                                CobsError::OutputTooSmall { needed: info.decoded_len },
+                            );
+                        }
+                    } else {
+                        let e = from_validate.unwrap_err();
+                        let short = e.output_len / 2;
+                        assert_eq!(
+                            options.decode_buf_detailed(&damaged, &mut out[..short]),
+                            Err(e),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn canonical_fixtures() {
+    let canonical = DecodeOptions::new().canonical(true);
+    for (expected, encoded) in all_fixtures() {
+        assert!(is_canonical(encoded), "{:x?}", encoded);
+
+        let mut out = vec![0; encoded.len()];
+        let d = canonical.decode_buf_with_consumed(encoded, &mut out).unwrap();
+        assert_eq!(&out[..d.decoded_len], expected);
+        assert_eq!(canonical.decode_in_place_with_consumed(&mut encoded.to_vec()), Ok(d));
+        let (a, b) = encoded.split_at(encoded.len() / 2);
+        let r = canonical.decode_ring_buf((a, b), &mut out).unwrap();
+        assert_eq!(r.decoded_len, d.decoded_len);
+
+        // Trailing data, or a missing terminator, isn't canonical.
+        let mut extra = encoded.to_vec();
+        extra.push(0x01);
+        assert!(!is_canonical(&extra));
+        assert!(!is_canonical(&encoded[..encoded.len() - 1]));
+    }
+}
+
+#[test]
+fn non_canonical_rejected() {
+    let mut long = vec![0xFF];
+    long.extend(1..=254);
+    long.extend([0x01, 0x00]);
+
+    let cases: [&[u8]; 3] = [
+        &[0x00],
+        &long,
+        &[0x03, 0x11, 0x00, 0x00],
+    ];
+    let canonical = DecodeOptions::new().canonical(true);
+    for (i, input) in cases.iter().enumerate() {
+        assert!(!is_canonical(input), "case {}", i);
+
+        let mut out = vec![0; input.len()];
+        // Without the check, these decode fine (or at least not as
+        // non-canonical).
+        assert_ne!(
+            DecodeOptions::new().decode_buf(input, &mut out),
+            Err(CobsError::NonCanonical),
+        );
+        let expected = if i == 2 {
+            CobsError::Corrupt
+        } else {
+            CobsError::NonCanonical
+        };
+        assert_eq!(canonical.decode_buf(input, &mut out), Err(expected), "case {}", i);
+        assert_eq!(canonical.decode_buf(input, &mut out[..1]), Err(expected), "case {}", i);
+        assert_eq!(canonical.decode_in_place(&mut input.to_vec()), Err(expected), "case {}", i);
+        for k in 0..input.len() {
+            let (a, b) = input.split_at(k);
+            assert_eq!(canonical.decode_ring_buf((a, b), &mut out), Err(expected), "case {}", i);
+        }
+    }
+
+    // The empty run after a maximal one is fine if it isn't the last.
+    let mut ok = long.clone();
+    ok.insert(ok.len() - 1, 0x01);
+    assert!(is_canonical(&ok));
+    let mut expected: Vec<u8> = (1..=254).collect();
+    expected.push(0);
+    let mut out = vec![0; ok.len()];
+    let n = canonical.decode_buf(&ok, &mut out).unwrap();
+    assert_eq!(&out[..n], &expected[..]);
+}
+
+#[test]
+fn decode_ring_buf_every_split() {
+    let fixtures = all_fixtures();
+    let stream = concatenated_fixture_stream();
+
+    // Rotate the stream around the ring at every possible point, and check
+    // that we can walk through all the messages.
+    for k in 0..=stream.len() {
+        let (mut first, mut second) = stream.split_at(k);
+        let mut out = vec![0; stream.len()];
+        for (i, (expected, encoded)) in fixtures.iter().enumerate() {
+            let d = decode_ring_buf((first, second), &mut out).unwrap();
+            assert_eq!(&out[..d.decoded_len], *expected, "case {} split {}", i, k);
+            assert_eq!(d.consumed.0 + d.consumed.1, encoded.len(), "case {} split {}", i, k);
+            assert!(d.consumed.1 == 0 || d.consumed.0 == first.len());
+            first = &first[d.consumed.0..];
+            second = &second[d.consumed.1..];
+            if first.is_empty() {
+                first = std::mem::take(&mut second);
+            }
+        }
+        assert!(first.is_empty() && second.is_empty());
+    }
+}
+
+#[test]
+fn decode_ring_buf_errors() {
+    let encoded = [0x03, 0x11, 0x22, 0x02, 0x33, 0x00];
+    for k in 0..=encoded.len() {
+        let halves = encoded.split_at(k);
+
+        let mut out = [0; 3];
+        assert_eq!(
+            decode_ring_buf(halves, &mut out),
+            Err(CobsError::OutputTooSmall { needed: 4 }),
+            "split {}", k,
+        );
+
+        let mut out = [0; 4];
+        let truncated = encoded[..encoded.len() - 1].split_at(k.min(encoded.len() - 1));
+        assert_eq!(
+            decode_ring_buf(truncated, &mut out),
+            Err(CobsError::Truncated),
+            "split {}", k,
+        );
+    }
+}
+
+#[test]
+fn decoder_feed_fixtures() {
+    for (i, (expected, encoded)) in all_fixtures().into_iter().enumerate() {
+        for in_chunk in [1, 2, 3, 254, 255, 1000] {
+            for out_chunk in [1, 2, 255, 1000] {
+                let mut decoder = Decoder::default();
+                let mut actual = vec![];
+                let mut input = encoded;
+                let mut buf = vec![0; out_chunk];
+                let mut done = false;
+                while !done {
+                    let chunk = &input[..in_chunk.min(input.len())];
+                    let r = decoder.feed(chunk, &mut buf).unwrap();
+                    actual.extend_from_slice(&buf[..r.produced]);
+                    input = &input[r.consumed..];
+                    done = r.frame_done;
+                }
+                assert!(input.is_empty());
+                assert_eq!(actual, expected, "case {} in {} out {}", i, in_chunk, out_chunk);
+            }
+        }
+    }
+}
+
+#[test]
+fn decoder_feed_stops_at_terminator() {
+    let mut decoder = Decoder::default();
+    let mut out = [0; 8];
+    let r = decoder.feed(&[0x02, 0x11, 0x00, 0x02, 0x22, 0x00], &mut out).unwrap();
+    assert_eq!(r, FeedResult { consumed: 3, produced: 1, frame_done: true });
+    assert!(decoder.is_done());
+}
+
+#[test]
+fn decoder_feed_corrupt() {
+    let mut decoder = Decoder::default();
+    let mut out = [0; 8];
+    assert_eq!(
+        decoder.feed(&[0x04, 0x11, 0x00], &mut out).map_err(CobsError::from),
+        Err(CobsError::Corrupt),
+    );
+}
+
+#[test]
+fn decoder_feed_error_progress() {
+    let mut decoder = Decoder::default();
+    let mut out = [0; 8];
+    assert_eq!(
+        decoder.feed(&[0x04, 0x11, 0x00, 0x02], &mut out),
+        Err(FeedError {
+            error: CobsError::Corrupt,
+            consumed: 3,
+            produced: 1,
+            offset: 2,
+            code: Some(0x04),
+            output_len: 1,
+        }),
+    );
+}
+
+#[test]
+fn decoder_error_offsets_span_feeds() {
+    // The frame's offset counts from its first byte, whichever call it came
+    // in, and skips anything dropped while hunting.
+    let mut decoder = Decoder::new().with_resync(true);
+    let mut out = [0; 8];
+    decoder.hunt();
+    decoder.feed(&[0x11, 0x00, 0x02, 0x22, 0x03], &mut out).unwrap();
+    let e = decoder.feed(&[0x33, 0x00], &mut out).unwrap_err();
+    assert_eq!((e.consumed, e.produced), (2, 1));
+    let e = DecodeError::from(e);
+    assert_eq!(
+        e,
+        DecodeError { kind: CobsError::Corrupt, offset: 4, code: Some(0x03), output_len: 3 },
+    );
+    assert_eq!(
+        e.to_string(),
+        "input corrupt at offset 4, in run with length byte 0x03, after 3 bytes of output",
+    );
+
+    // The byte-at-a-time path reports the same thing.
+    let mut decoder = Decoder::new();
+    let results: Vec<_> = [0x02, 0x22, 0x03, 0x33, 0x00]
+        .iter()
+        .map(|&b| decoder.advance_detailed(b))
+        .collect();
+    assert_eq!(results.last(), Some(&Err(e)));
+}
+
+#[test]
+fn decoder_resync_reuses_after_done() {
+    let mut decoder = Decoder::new().with_resync(true);
+    let stream = [0x02, 0x11, 0x00, 0x01, 0x00, 0x03, 0x22, 0x33, 0x00];
+    let mut frames = vec![];
+    let mut frame = vec![];
+    for &b in &stream {
+        match decoder.advance(b).unwrap() {
+            DecodeStatus::Append(x) => frame.push(x),
+            DecodeStatus::Pending => (),
+            DecodeStatus::Done => frames.push(core::mem::take(&mut frame)),
+        }
+    }
+    assert_eq!(frames, [&[0x11][..], &[], &[0x22, 0x33]]);
+    assert_eq!(decoder.skipped(), 0);
+}
+
+#[test]
+fn decoder_resync_feed_stream() {
+    let mut stream = vec![0x05, 0x11, 0x22, 0x00]; // cut short
+    stream.extend(concatenated_fixture_stream());
+
+    for chunk in [1, 2, 3, 254, 255, 10000] {
+        let mut decoder = Decoder::new().with_resync(true);
+        let mut out = vec![0; 2000];
+        let mut produced = 0;
+        let mut frames = vec![];
+        let mut errors = 0;
+        for mut piece in stream.chunks(chunk) {
+            while !piece.is_empty() {
+                match decoder.feed(piece, &mut out[produced..]) {
+                    Ok(r) => {
+                        produced += r.produced;
+                        piece = &piece[r.consumed..];
+                        if r.frame_done {
+                            frames.push(out[..produced].to_vec());
+                            produced = 0;
+                        }
+                    }
+                    Err(e) => {
+                        assert_eq!(e.error, CobsError::Corrupt);
+                        errors += 1;
+                        produced = 0;
+                        piece = &piece[e.consumed..];
+                    }
+                }
+            }
+        }
+        let expected: Vec<&[u8]> = all_fixtures().iter().map(|(d, _)| *d).collect();
+        assert_eq!(frames, expected, "chunk {}", chunk);
+        assert_eq!(errors, 1);
+    }
+}
+
+#[test]
+fn decoder_max_len() {
+    for (expected, encoded) in all_fixtures() {
+        for max_len in [0, 1, 253, 254, 255, 1000] {
+            let fits = expected.len() <= max_len;
+
+            let mut decoder = Decoder::new().with_max_len(max_len);
+            let mut stepped = Ok(());
+            for &b in encoded {
+                match decoder.advance(b) {
+                    Ok(DecodeStatus::Done) => break,
+                    Ok(_) => (),
+                    Err(e) => {
+                        stepped = Err(e);
+                        break;
+                    }
+                }
+            }
+
+            let mut decoder = Decoder::new().with_max_len(max_len);
+            let mut out = vec![0; 2000];
+            let fed = decoder.feed(encoded, &mut out).map(|r| r.produced);
+
+            if fits {
+                assert_eq!(stepped, Ok(()));
+                assert_eq!(fed, Ok(expected.len()));
+            } else {
+                assert_eq!(stepped, Err(CobsError::TooLong));
+                assert_eq!(
+                    fed.map_err(|e| (e.error, e.produced)),
+                    Err((CobsError::TooLong, max_len)),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn decoder_max_len_resync() {
+    let mut decoder = Decoder::new().with_resync(true).with_max_len(2);
+    let mut out = [0; 8];
+    let input = [0x04, 0x11, 0x22, 0x33, 0x00, 0x02, 0x44, 0x00];
+    let e = decoder.feed(&input, &mut out).unwrap_err();
+    assert_eq!(e.error, CobsError::TooLong);
+    assert_eq!(e.consumed, 4);
+    assert!(decoder.is_hunting());
+    let r = decoder.feed(&input[e.consumed..], &mut out).unwrap();
+    assert_eq!(r, FeedResult { consumed: 4, produced: 1, frame_done: true });
+    assert_eq!(out[0], 0x44);
+    assert_eq!(decoder.skipped(), 0);
+}
+
+#[test]
+fn decoder_hunt_counts_skipped() {
+    let mut decoder = Decoder::new().with_resync(true);
+    decoder.hunt();
+    assert!(decoder.is_hunting());
+    let mut out = [0; 8];
+    let r = decoder.feed(&[0x11, 0x22], &mut out).unwrap();
+    assert_eq!(r, FeedResult { consumed: 2, produced: 0, frame_done: false });
+    let r = decoder.feed(&[0x33, 0x00, 0x02, 0x44, 0x00], &mut out).unwrap();
+    assert_eq!(r, FeedResult { consumed: 5, produced: 1, frame_done: true });
+    assert_eq!(out[0], 0x44);
+    assert_eq!(decoder.skipped(), 3);
+    assert!(!decoder.is_hunting());
+
+    // Byte at a time gives the same count.
+    decoder.hunt();
+    for &b in &[0x55, 0x66, 0x00] {
+        assert_eq!(decoder.advance(b), Ok(DecodeStatus::Pending));
+    }
+    assert_eq!(decoder.skipped(), 5);
+
+    // Resetting drops the partial frame but keeps the count.
+    decoder.advance(0x03).unwrap();
+    decoder.reset();
+    assert_eq!(decoder.advance(0x01), Ok(DecodeStatus::Pending));
+    assert_eq!(decoder.advance(0x00), Ok(DecodeStatus::Done));
+    assert_eq!(decoder.skipped(), 5);
+}
+
+#[test]
+fn frame_receiver_fixtures() {
+    let stream = concatenated_fixture_stream();
+    let expected: Vec<&[u8]> = all_fixtures().iter().map(|(d, _)| *d).collect();
+    for chunk in [1, 255, 10000] {
+        let mut rx = FrameReceiver::<256>::new();
+        let mut frames = vec![];
+        for piece in stream.chunks(chunk) {
+            rx.push_all(piece, |f| frames.push(f.unwrap().to_vec()));
+        }
+        assert_eq!(frames, expected, "chunk {}", chunk);
+    }
+}
+
+#[test]
+fn frame_receiver_errors() {
+    let stream = [
+        0x04, 0x11, 0x00, // interrupted
+        0x05, 0x11, 0x22, 0x33, 0x44, 0x00, // too big
+        0x03, 0x11, 0x22, 0x00, // just right
+    ];
+    for chunk in [1, 2, 100] {
+        let mut storage = [0; 3];
+        let mut rx = FrameReceiverRef::new(&mut storage);
+        let mut results = vec![];
+        for piece in stream.chunks(chunk) {
+            rx.push_all(piece, |f| results.push(f.map(|f| f.to_vec())));
+        }
+        assert_eq!(results, [
+            Err(DecodeError {
+                kind: CobsError::Corrupt,
+                offset: 2,
+                code: Some(0x04),
+                output_len: 1,
+            }),
+            Err(DecodeError {
+                kind: CobsError::OutputTooSmall { needed: 4 },
+                offset: 4,
+                code: Some(0x05),
+                output_len: 3,
+            }),
+            Ok(vec![0x11, 0x22]),
+        ], "chunk {}", chunk);
+    }
+}
+
+#[test]
+fn frame_receiver_max_len() {
+    let stream = [
+        0x05, 0x11, 0x22, 0x33, 0x44, 0x00, // too long
+        0x03, 0x11, 0x22, 0x00, // just right
+    ];
+    for chunk in [1, 2, 100] {
+        let mut rx = FrameReceiver::<16>::new().with_max_len(2);
+        let mut results = vec![];
+        for piece in stream.chunks(chunk) {
+            rx.push_all(piece, |f| results.push(f.map(|f| f.to_vec())));
+        }
+        assert_eq!(results, [
+            Err(DecodeError {
+                kind: CobsError::TooLong,
+                offset: 3,
+                code: Some(0x05),
+                output_len: 2,
+            }),
+            Ok(vec![0x11, 0x22]),
+        ], "chunk {}", chunk);
+    }
+}
+
+#[test]
+fn frame_receiver_push_stops_at_frame() {
+    let mut rx = FrameReceiver::<8>::new();
+    let input = [0x02, 0x11, 0x00, 0x02, 0x22, 0x00];
+    let (n, frame) = rx.push(&input);
+    assert_eq!(n, 3);
+    assert_eq!(frame, Some(Ok(&[0x11][..])));
+    let (n, frame) = rx.push(&input[3..]);
+    assert_eq!(n, 3);
+    assert_eq!(frame, Some(Ok(&[0x22][..])));
+}
+
+/// Checks `Cobs<D>` against the fixtures, by comparing it to XORing with `D`
+/// before and after standard COBS.
+fn check_delimiter<const D: u8>() {
+    for (input, encoded) in all_fixtures() {
+        let xored: Vec<u8> = input.iter().map(|b| b ^ D).collect();
+        let mut expected = vec![0; max_encoded_len(input.len())];
+        let n = encode_buf(&xored, &mut expected);
+        expected.truncate(n);
+        for b in &mut expected {
+            *b ^= D;
+        }
+        if D == 0 {
+            assert_eq!(expected, encoded);
+        }
+        assert_eq!(expected.iter().position(|&b| b == D), Some(n - 1));
+
+        let mut actual = vec![0; max_encoded_len(input.len())];
+        let n = Cobs::<D>::encode_buf(input, &mut actual);
+        assert_eq!(&actual[..n], &expected[..], "encode_buf, delimiter {:#x}", D);
+
+        let actual: Vec<u8> = Cobs::<D>::encode_iter(input).collect();
+        assert_eq!(actual, expected, "encode_iter, delimiter {:#x}", D);
+
+        let mut buf = vec![0; max_encoded_len(input.len())];
+        let start = buf.len() - input.len();
+        buf[start..].copy_from_slice(input);
+        let n = Cobs::<D>::encode_in_place(&mut buf, input.len());
+        assert_eq!(&buf[..n], &expected[..], "encode_in_place, delimiter {:#x}", D);
+
+        let mut decoded = vec![0; expected.len()];
+        let d = Cobs::<D>::decode_buf_with_consumed(&expected, &mut decoded).unwrap();
+        assert_eq!(&decoded[..d.decoded_len], input, "decode_buf, delimiter {:#x}", D);
+        assert_eq!(d.consumed, expected.len());
+
+        let mut buf = expected.clone();
+        let n = Cobs::<D>::decode_in_place(&mut buf).unwrap();
+        assert_eq!(&buf[..n], input, "decode_in_place, delimiter {:#x}", D);
+
+        let mut decoder = Cobs::<D>::decoder();
+        let mut actual = vec![];
+        for &b in &expected {
+            match decoder.advance(b).unwrap() {
+                DecodeStatus::Pending => (),
+                DecodeStatus::Append(b) => actual.push(b),
+                DecodeStatus::Done => break,
+            }
+        }
+        assert_eq!(actual, input, "Decoder, delimiter {:#x}", D);
+
+        let mut decoder = Cobs::<D>::decoder();
+        let r = decoder.feed(&expected, &mut decoded).unwrap();
+        assert!(r.frame_done);
+        assert_eq!(&decoded[..r.produced], input, "Decoder::feed, delimiter {:#x}", D);
+    }
+}
+
+#[test]
+fn delimiter_fixtures() {
+    check_delimiter::<0x00>();
+    check_delimiter::<0x01>();
+    check_delimiter::<0x7E>();
+    check_delimiter::<0xFE>();
+    check_delimiter::<0xFF>();
+}
+
+#[test]
+fn delimiter_errors() {
+    let mut out = [0; 8];
+    // A bare delimiter is an empty frame, and a zero is just data.
+    assert_eq!(Cobs::<0xFF>::decode_buf(&[0xFF], &mut out), Ok(0));
+    assert_eq!(Cobs::<0xFF>::decode_buf(&[0xFD, 0x00, 0xFF], &mut out), Ok(1));
+    assert_eq!(out[0], 0x00);
+    // Running out of input is still truncation.
+    assert_eq!(Cobs::<0xFF>::decode_buf(&[0xFC, 0x11], &mut out), Err(CobsError::Truncated));
+    // The decoder spots the delimiter inside a run.
+    let mut decoder = Cobs::<0xFF>::decoder().with_resync(true);
+    let e = decoder.feed(&[0xFB, 0x11, 0xFF, 0xFD, 0x22, 0xFF], &mut out).unwrap_err();
+    assert_eq!((e.error, e.consumed), (CobsError::Corrupt, 3));
+    let r = decoder.feed(&[0xFD, 0x22, 0xFF], &mut out).unwrap();
+    assert!(r.frame_done);
+    assert_eq!(&out[..r.produced], [0x22]);
+}
+
+/// COBS/R fixtures, from the reference implementation's test suite (with the
+/// terminator added).
+static COBSR_FIXTURES: &[(&[u8], &[u8])] = &[
+    (&[], &[0x01, 0x00]),
+    (&[0x00], &[0x01, 0x01, 0x00]),
+    (&[0x01], &[0x02, 0x01, 0x00]),
+    (&[0x02], &[0x02, 0x00]),
+    (b"1", b"1\x00"),
+    (b"12345", b"51234\x00"),
+    (b"12345\x00\x04", b"\x0612345\x04\x00"),
+    (b"12345\x00\x01", b"\x0612345\x02\x01\x00"),
+    (b"12345\x00", b"\x0612345\x01\x00"),
+    (&[0x11, 0x22, 0x00, 0x33], &[0x03, 0x11, 0x22, 0x33, 0x00]),
+];
+
+#[test]
+fn cobsr_fixtures() {
+    for (i, (input, expected)) in COBSR_FIXTURES.iter().enumerate() {
+        let mut actual = vec![0; max_encoded_len(input.len())];
+        let n = cobsr::encode_buf(input, &mut actual);
+        assert_eq!(&actual[..n], *expected, "encode_buf, fixture {}", i);
+
+        let mut buf = vec![0; max_encoded_len(input.len())];
+        let start = buf.len() - input.len();
+        buf[start..].copy_from_slice(input);
+        let n = cobsr::encode_in_place(&mut buf, input.len());
+        assert_eq!(&buf[..n], *expected, "encode_in_place, fixture {}", i);
+
+        let mut decoded = vec![0; expected.len()];
+        let d = cobsr::decode_buf_with_consumed(expected, &mut decoded).unwrap();
+        assert_eq!(&decoded[..d.decoded_len], *input, "decode_buf, fixture {}", i);
+        assert_eq!(d.consumed, expected.len());
+
+        let mut buf = expected.to_vec();
+        let n = cobsr::decode_in_place(&mut buf).unwrap();
+        assert_eq!(&buf[..n], *input, "decode_in_place, fixture {}", i);
+    }
+}
+
+#[test]
+fn cobsr_round_trip() {
+    let mut inputs: Vec<Vec<u8>> = all_fixtures().iter().map(|(i, _)| i.to_vec()).collect();
+    // Final runs of every length around the maximum, ending in big and small
+    // bytes.
+    for len in 250..=510 {
+        for last in [0x01, 0xFE, 0xFF] {
+            let mut input = vec![0xAA; len];
+            input[len - 1] = last;
+            inputs.push(input);
+        }
+    }
+    for input in &inputs {
+        let mut standard = vec![0; max_encoded_len(input.len())];
+        let std_len = encode_buf(input, &mut standard);
+
+        let mut encoded = vec![0; max_encoded_len(input.len())];
+        let n = cobsr::encode_buf(input, &mut encoded);
+        assert!(n == std_len || n == std_len - 1);
+        assert_eq!(encoded.iter().position(|&b| b == 0), Some(n - 1));
+
+        let mut decoded = vec![0; n];
+        assert_eq!(cobsr::decode_buf(&encoded[..n], &mut decoded), Ok(input.len()));
+        assert_eq!(&decoded[..input.len()], &input[..]);
+
+        let mut buf = encoded[..n].to_vec();
+        assert_eq!(cobsr::decode_in_place(&mut buf), Ok(input.len()));
+        assert_eq!(&buf[..input.len()], &input[..]);
+
+        // Standard encodings decode the same way.
+        assert_eq!(cobsr::decode_buf(&standard[..std_len], &mut decoded), Ok(input.len()));
+        assert_eq!(&decoded[..input.len()], &input[..]);
+    }
+}
+
+#[test]
+fn cobsr_errors() {
+    let mut out = [0; 8];
+    assert_eq!(cobsr::decode_buf(&[], &mut out), Err(CobsError::Truncated));
+    assert_eq!(cobsr::decode_buf(&[0x03, 0x11], &mut out), Err(CobsError::Truncated));
+    assert_eq!(cobsr::decode_in_place(&mut [0x33, 0x11, 0x22]), Err(CobsError::Truncated));
+    // The zero in the first run ends the message there, so the rest is
+    // ignored, as with any trailing data.
+    let d = cobsr::decode_buf_with_consumed(&[0x05, 0x11, 0x00, 0x02, 0x22, 0x00], &mut out);
+    assert_eq!(d, Ok(Decoded { decoded_len: 2, consumed: 3 }));
+    assert_eq!(&out[..2], [0x11, 0x05]);
+    assert_eq!(
+        cobsr::decode_buf(&[0x04, 0x11, 0x22, 0x33, 0x00], &mut out[..2]),
+        Err(CobsError::OutputTooSmall { needed: 3 }),
+    );
+}
+
+static ZPE_FIXTURES: &[(&[u8], &[u8])] = &[
+    (&[], &[0x01, 0x00]),
+    (&[0x00], &[0xE1, 0x00]),
+    (&[0x00, 0x00], &[0xE1, 0x01, 0x00]),
+    (&[0x00, 0x00, 0x00], &[0xE1, 0xE1, 0x00]),
+    (
+        &[0x11, 0x22, 0x00, 0x33],
+        &[0x03, 0x11, 0x22, 0x02, 0x33, 0x00],
+    ),
+    (
+        &[0x11, 0x00, 0x00, 0x00],
+        &[0xE2, 0x11, 0xE1, 0x00],
+    ),
+    (
+        &[0x11, 0x22, 0x00, 0x00, 0x33],
+        &[0xE3, 0x11, 0x22, 0x02, 0x33, 0x00],
+    ),
+];
+
+/// Decodes `encoded` by passing it to `feed`, a streaming decoder's `feed`
+/// method, `chunk` symbols at a time and with `chunk` symbols of output at a
+/// time.
+fn feed_in_chunks<S: symbol::Symbol>(
+    encoded: &[S],
+    chunk: usize,
+    mut feed: impl FnMut(&[S], &mut [S]) -> Result<FeedResult, FeedError<S>>,
+) -> Result<Vec<S>, CobsError> {
+    let mut input = encoded;
+    let mut decoded = vec![];
+    let mut out = vec![S::ZERO; chunk];
+    loop {
+        let r = feed(&input[..chunk.min(input.len())], &mut out)?;
+        decoded.extend_from_slice(&out[..r.produced]);
+        input = &input[r.consumed..];
+        if r.frame_done {
+            return Ok(decoded);
+        }
+        if input.is_empty() {
+            return Err(CobsError::Truncated);
+        }
+    }
+}
+
+/// A streaming decoder's `feed` method.
+type FeedFn<D, S> = fn(&mut D, &[S], &mut [S]) -> Result<FeedResult, FeedError<S>>;
+
+/// Checks that `encoded` is a single frame that decodes to `input`, using
+/// `decode_buf` with `decoded_len` symbols of output, and using streaming
+/// decoders from `decoder`, fed in chunks of various sizes.
+fn check_decodes<S: symbol::Symbol, D>(
+    input: &[S],
+    encoded: &[S],
+    decoded_len: usize,
+    decode_buf: fn(&[S], &mut [S]) -> Result<Decoded, CobsError>,
+    decoder: fn() -> D,
+    feed: FeedFn<D, S>,
+) {
+    let n = encoded.len();
+    assert_eq!(encoded.iter().position(|&s| s == S::ZERO), Some(n - 1));
+
+    let mut decoded = vec![S::ZERO; decoded_len];
+    let d = decode_buf(encoded, &mut decoded).unwrap();
+    assert_eq!(&decoded[..d.decoded_len], input);
+    assert_eq!(d.consumed, n);
+
+    for chunk in [1, 2, 7, 300, 70000] {
+        let mut decoder = decoder();
+        let actual = feed_in_chunks(encoded, chunk, |i, o| feed(&mut decoder, i, o));
+        assert_eq!(actual.as_deref(), Ok(input), "chunk {}", chunk);
+    }
+}
+
+fn check_zpe_round_trip(input: &[u8]) {
+    let mut encoded = vec![0; zpe::max_encoded_len(input.len())];
+    let n = zpe::encode_buf(input, &mut encoded);
+    check_decodes(
+        input,
+        &encoded[..n],
+        zpe::max_decoded_len(n),
+        zpe::decode_buf_with_consumed,
+        zpe::Decoder::new,
+        zpe::Decoder::feed,
+    );
+}
+
+#[test]
+fn zpe_fixtures() {
+    for (i, (input, expected)) in ZPE_FIXTURES.iter().enumerate() {
+        let mut actual = vec![0; zpe::max_encoded_len(input.len())];
+        let n = zpe::encode_buf(input, &mut actual);
+        assert_eq!(&actual[..n], *expected, "mismatch in test fixture case {}", i);
+        check_zpe_round_trip(input);
+    }
+}
+
+#[test]
+fn zpe_round_trip() {
+    for (input, _) in all_fixtures() {
+        check_zpe_round_trip(input);
+    }
+    // Runs around each of the length limits, followed by nothing, a zero, or
+    // a pair of zeroes.
+    for len in (0..40).chain(215..=230).chain(440..=450) {
+        for tail in [&[][..], &[0x00], &[0x00, 0x00], &[0x00, 0x00, 0x00, 0x11]] {
+            let mut input = vec![0xAA; len];
+            input.extend_from_slice(tail);
+            check_zpe_round_trip(&input);
+        }
+    }
+}
+
+#[test]
+fn zpe_sizes() {
+    // No zeroes is the worst case for encoding...
+    for len in [0, 1, 222, 223, 224, 446, 447, 1000] {
+        let input = vec![0xFF; len];
+        let mut encoded = vec![0; zpe::max_encoded_len(len)];
+        assert_eq!(zpe::encode_buf(&input, &mut encoded), encoded.len(), "len {}", len);
+    }
+    // ...and all zeroes is the best, and the worst case for decoding.
+    let input = [0; 1024];
+    let mut encoded = [0; zpe::max_encoded_len(1024)];
+    let n = zpe::encode_buf(&input, &mut encoded);
+    assert_eq!(n, 514);
+    for odd in [1, 3, 1023] {
+        let mut encoded = vec![0; zpe::max_encoded_len(odd)];
+        let n = zpe::encode_buf(&input[..odd], &mut encoded);
+        assert_eq!(zpe::max_decoded_len(n), odd);
+    }
+}
+
+#[test]
+fn zpe_errors() {
+    let mut out = [0; 8];
+    assert_eq!(zpe::decode_buf(&[], &mut out), Err(CobsError::Truncated));
+    assert_eq!(zpe::decode_buf(&[0x03, 0x11], &mut out), Err(CobsError::Truncated));
+    assert_eq!(
+        zpe::decode_buf(&[0xE1, 0xE1, 0x00], &mut out[..2]),
+        Err(CobsError::OutputTooSmall { needed: 3 }),
+    );
+    assert_eq!(
+        zpe::decode_buf(&[0xE1, 0xE1, 0xE1, 0x02, 0x11, 0x00], &mut out[..2]),
+        Err(CobsError::OutputTooSmall { needed: 7 }),
+    );
+
+    let mut decoder = zpe::Decoder::new();
+    let e = decoder.feed(&[0x04, 0x11, 0x00, 0x22, 0x00], &mut out).unwrap_err();
+    assert_eq!(
+        e,
+        FeedError {
+            error: CobsError::Corrupt,
+            consumed: 3,
+            produced: 1,
+            offset: 2,
+            code: Some(0x04),
+            output_len: 1,
+        },
+    );
+    // A failed frame isn't done, and feeding more doesn't panic.
+    assert!(!decoder.is_done());
+    decoder.reset();
+    let r = feed_in_chunks(&[0xE1, 0x00], 1, |i, o| decoder.feed(i, o));
+    assert_eq!(r, Ok(vec![0x00]));
+    assert!(decoder.is_done());
+}
+
+#[test]
+fn zpe_feed_after_corrupt_without_resync() {
+    // Like the crate's `Decoder`, the zpe decoder keeps reporting errors for
+    // the broken frame, rather than panicking or claiming to be done.
+    let mut out = [0; 8];
+    let mut plain = Decoder::new();
+    let mut decoder = zpe::Decoder::new();
+    for input in [&[0x05, 0x11, 0x00][..], &[0x02, 0x11, 0x00]] {
+        plain.feed(input, &mut out).unwrap_err();
+        let e = decoder.feed(input, &mut out).unwrap_err();
+        assert_eq!((e.error, e.consumed), (CobsError::Corrupt, 3));
+        assert!(!plain.is_done());
+        assert!(!decoder.is_done());
+    }
+}
+
+#[test]
+fn zpe_decoders_agree() {
+    // Given the whole frame, `decode_buf` should accept exactly what the
+    // `Decoder` does, including rejecting zeroes in runs.
+    let check = |input: &[u8]| {
+        let mut out = vec![0; zpe::max_decoded_len(input.len())];
+        let buf = zpe::decode_buf_with_consumed(input, &mut out)
+            .map(|d| (out[..d.decoded_len].to_vec(), d.consumed));
+        let stream = match zpe::Decoder::new().feed(input, &mut out) {
+            Ok(r) if r.frame_done => Ok((out[..r.produced].to_vec(), r.consumed)),
+            Ok(_) => Err(CobsError::Truncated),
+            Err(e) => Err(e.error),
+        };
+        assert_eq!(buf, stream, "input {:x?}", input);
+    };
+    for (input, encoded) in ZPE_FIXTURES {
+        check(encoded);
+        let mut out = vec![0; input.len()];
+        assert_eq!(zpe::decode_buf(encoded, &mut out), Ok(input.len()));
+        for i in 0..encoded.len() - 1 {
+            let mut damaged = encoded.to_vec();
+            damaged[i] = 0;
+            check(&damaged);
+            check(&encoded[..i]);
+        }
+    }
+    let mut out = [0; 8];
+    assert_eq!(zpe::decode_buf(&[0xE3, 0x11, 0x00], &mut out), Err(CobsError::Corrupt));
+}
+
+#[test]
+fn zpe_decoder_resync() {
+    let stream = [
+        0x11, 0x00, // tail end of a frame we missed
+        0xE3, 0x11, 0x00, // interrupted
+        0xE1, 0x00, // two zeroes, one real
+        0x02, 0x22, 0x00,
+    ];
+    for chunk in [1, 2, 100] {
+        let mut decoder = zpe::Decoder::new().with_resync(true);
+        decoder.hunt();
+        let mut results = vec![];
+        let mut frame = vec![];
+        for piece in stream.chunks(chunk) {
+            let mut input = piece;
+            while !input.is_empty() {
+                let mut out = [0; 4];
+                match decoder.feed(input, &mut out) {
+                    Ok(r) => {
+                        frame.extend_from_slice(&out[..r.produced]);
+                        input = &input[r.consumed..];
+                        if r.frame_done {
+                            results.push(Ok(core::mem::take(&mut frame)));
+                        }
+                    }
+                    Err(e) => {
+                        frame.clear();
+                        input = &input[e.consumed..];
+                        results.push(Err(e.error));
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            results,
+            [Err(CobsError::Corrupt), Ok(vec![0x00]), Ok(vec![0x22])],
+            "chunk {}",
+            chunk,
+        );
+        assert_eq!(decoder.skipped(), 1);
+    }
+}
+
+/// Converts a standard COBS encoding into rCOBS, by moving each length byte
+/// to the end of its run.
+fn to_rcobs(encoded: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    let mut rest = &encoded[..encoded.len() - 1];
+    while let Some((&code, tail)) = rest.split_first() {
+        let (run, tail) = tail.split_at(usize::from(code) - 1);
+        out.extend_from_slice(run);
+        out.push(code);
+        rest = tail;
+    }
+    out.push(0);
+    out
+}
+
+#[test]
+fn rcobs_fixtures() {
+    assert_eq!(to_rcobs(&[0x03, 0x11, 0x22, 0x02, 0x33, 0x00]), [0x11, 0x22, 0x03, 0x33, 0x02, 0x00]);
+
+    for (input, encoded) in all_fixtures() {
+        let expected = to_rcobs(encoded);
+
+        let mut actual = vec![0; max_encoded_len(input.len())];
+        let n = rcobs::encode_buf(input, &mut actual);
+        assert_eq!(&actual[..n], &expected[..], "encode_buf");
+
+        let actual: Vec<u8> = rcobs::encode_iter(input).collect();
+        assert_eq!(actual, expected, "encode_iter");
+
+        for chunk in [1, 3, 300] {
+            let mut actual = vec![];
+            let mut enc = rcobs::Encoder::new(|b: &[u8]| actual.extend_from_slice(b));
+            for piece in input.chunks(chunk) {
+                enc.push(piece);
+            }
+            enc.finish();
+            assert_eq!(actual, expected, "Encoder, chunk {}", chunk);
+        }
+        let mut actual = vec![];
+        let mut enc = rcobs::Encoder::new(|b: &[u8]| actual.extend_from_slice(b));
+        input.iter().for_each(|&b| enc.push_byte(b));
+        enc.finish();
+        assert_eq!(actual, expected, "Encoder::push_byte");
+
+        let mut decoded = vec![0; expected.len()];
+        let d = rcobs::decode_buf_with_consumed(&expected, &mut decoded).unwrap();
+        assert_eq!(&decoded[..d.decoded_len], input, "decode_buf");
+        assert_eq!(d.consumed, expected.len());
+
+        let mut buf = expected.clone();
+        let n = rcobs::decode_in_place(&mut buf).unwrap();
+        assert_eq!(&buf[..n], input, "decode_in_place");
+    }
+}
+
+#[test]
+fn rcobs_encoder_reuse() {
+    let mut out = vec![];
+    let mut enc = rcobs::Encoder::new(|b: &[u8]| out.extend_from_slice(b));
+    enc.push(&[0xAA; 254]);
+    enc.end_frame();
+    enc.push(&[0x11]);
+    enc.finish();
+    assert_eq!(out.len(), 256 + 3);
+    assert_eq!(&out[254..], [0xFF, 0x00, 0x11, 0x02, 0x00]);
+
+    // Two frames back to back decode one at a time.
+    let mut decoded = [0; 300];
+    let d = rcobs::decode_buf_with_consumed(&out, &mut decoded).unwrap();
+    assert_eq!((d.decoded_len, d.consumed), (254, 256));
+    let d = rcobs::decode_buf_with_consumed(&out[256..], &mut decoded).unwrap();
+    assert_eq!((d.decoded_len, d.consumed), (1, 3));
+}
+
+#[test]
+fn rcobs_errors() {
+    let mut out = [0; 8];
+    // No terminator.
+    assert_eq!(rcobs::decode_buf(&[0x11, 0x02], &mut out), Err(CobsError::Truncated));
+    assert_eq!(rcobs::decode_in_place(&mut [0x11, 0x02]), Err(CobsError::Truncated));
+    // The start of the message is missing.
+    assert_eq!(rcobs::decode_buf(&[0x22, 0x03, 0x00], &mut out), Err(CobsError::Truncated));
+    assert_eq!(rcobs::decode_in_place(&mut [0x22, 0x03, 0x00]), Err(CobsError::Truncated));
+    assert_eq!(
+        rcobs::decode_buf(&[0x11, 0x22, 0x03, 0x33, 0x02, 0x00], &mut out[..3]),
+        Err(CobsError::OutputTooSmall { needed: 4 }),
+    );
+    // A bare terminator is an empty message, and an empty run after a maximal
+    // one decodes to nothing, as in standard COBS.
+    assert_eq!(rcobs::decode_buf(&[0x00], &mut out), Ok(0));
+    let mut input = vec![0xAA; 254];
+    input.extend_from_slice(&[0xFF, 0x01, 0x00]);
+    let mut decoded = [0; 300];
+    assert_eq!(rcobs::decode_buf(&input, &mut decoded), Ok(254));
+}
+
+fn check_symbol_round_trip<S: symbol::Symbol>(input: &[S]) -> Vec<S> {
+    let mut encoded = vec![S::ZERO; symbol::max_encoded_len::<S>(input.len())];
+    let n = symbol::encode_buf(input, &mut encoded);
+    encoded.truncate(n);
+    assert_eq!(n, symbol::encoded_len(input));
+    check_decodes(
+        input,
+        &encoded,
+        n,
+        symbol::decode_buf_with_consumed,
+        symbol::decoder,
+        symbol::Decoder::feed,
+    );
+
+    let mut buf = encoded.clone();
+    let len = symbol::decode_in_place(&mut buf).unwrap();
+    assert_eq!(&buf[..len], input);
+    encoded
+}
+
+#[test]
+fn symbol_u8_matches_bytes() {
+    for (input, expected) in all_fixtures() {
+        assert_eq!(check_symbol_round_trip(input), expected);
+    }
+    assert_eq!(symbol::max_encoded_len::<u8>(1000), max_encoded_len(1000));
+}
+
+#[test]
+fn symbol_u16_fixtures() {
+    let fixtures: &[(&[u16], &[u16])] = &[
+        (&[], &[0x0001, 0x0000]),
+        (&[0x0000], &[0x0001, 0x0001, 0x0000]),
+        (&[0x0100], &[0x0002, 0x0100, 0x0000]),
+        (
+            &[0x1111, 0x2222, 0x0000, 0x3333],
+            &[0x0003, 0x1111, 0x2222, 0x0002, 0x3333, 0x0000],
+        ),
+    ];
+    for (input, expected) in fixtures {
+        assert_eq!(check_symbol_round_trip(input), *expected);
+    }
+}
+
+#[test]
+fn symbol_u16_long_runs() {
+    for len in [65533, 65534, 65535, 65534 * 2, 65534 * 2 + 1] {
+        let input = vec![0xABCD_u16; len];
+        let encoded = check_symbol_round_trip(&input);
+        // No zeroes is the worst case.
+        assert_eq!(encoded.len(), symbol::max_encoded_len::<u16>(len));
+        assert_eq!(encoded[0], symbol::Symbol::encode_len(len.min(65534)));
+
+        let mut input = input;
+        input.push(0);
+        check_symbol_round_trip(&input);
+    }
+}
+
+#[test]
+fn symbol_sizes() {
+    for len in [0, 1, 253, 254, 255, 1000] {
+        assert_eq!(symbol::checked_max_encoded_len::<u8>(len), checked_max_encoded_len(len));
+        assert_eq!(symbol::min_encoded_len::<u8>(len), min_encoded_len(len));
+        assert_eq!(symbol::max_decoded_len::<u8>(len), max_decoded_len(len));
+    }
+    for len in [0, 1, 65533, 65534, 65535, 65534 * 2 + 1] {
+        let input = vec![0xABCD_u16; len];
+        let encoded = symbol::encoded_len(&input);
+        assert_eq!(symbol::checked_max_encoded_len::<u16>(len), Some(encoded));
+        assert!(symbol::max_decoded_len::<u16>(encoded) >= len);
+
+        // With a zero in every run, there's no overhead beyond the minimum.
+        let input: Vec<u16> = (0..len).map(|i| (i % 2) as u16).collect();
+        assert_eq!(symbol::encoded_len(&input), symbol::min_encoded_len::<u16>(len));
+        assert_eq!(symbol::checked_min_encoded_len::<u16>(len), Some(len + 2));
+    }
+    assert_eq!(symbol::checked_max_encoded_len::<u16>(usize::MAX), None);
+    assert_eq!(symbol::checked_max_encoded_len::<u16>(usize::MAX - 2), None);
+    assert_eq!(symbol::checked_min_encoded_len::<u16>(usize::MAX - 1), None);
+}
+
+#[test]
+fn symbol_errors() {
+    let mut out = [0_u16; 4];
+    assert_eq!(symbol::decode_buf::<u16>(&[0x0003, 0x1111], &mut out), Err(CobsError::Truncated));
+    assert_eq!(symbol::decode_buf::<u16>(&[0x0002, 0x1111], &mut out), Err(CobsError::Truncated));
+    assert_eq!(
+        symbol::decode_buf::<u16>(&[0x0003, 0x1111, 0x2222, 0x0002, 0x3333, 0x0000], &mut out[..2]),
+        Err(CobsError::OutputTooSmall { needed: 4 }),
+    );
+
+    let mut decoder = symbol::decoder::<u16>();
+    let e = decoder.feed(&[0x0004, 0x1111, 0x0000, 0x2222], &mut out).unwrap_err();
+    assert_eq!(
+        e,
+        FeedError {
+            error: CobsError::Corrupt,
+            consumed: 3,
+            produced: 1,
+            offset: 2,
+            code: Some(0x0004),
+            output_len: 1,
+        },
+    );
+    decoder.reset();
+    let r = decoder.feed(&[0x0002, 0x1111, 0x0000], &mut out).unwrap();
+    assert!(r.frame_done && decoder.is_done());
+}